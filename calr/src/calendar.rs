@@ -0,0 +1,98 @@
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// When the Julian calendar gave way to the Gregorian one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Reform {
+    /// Julian until 2 September 1752, Gregorian from 14 September 1752, as in Great Britain
+    #[default]
+    #[value(name = "1752")]
+    Y1752,
+    /// Gregorian throughout (proleptic), as ISO 8601
+    Iso,
+    /// Julian throughout, never reformed
+    None,
+}
+
+const DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+impl Reform {
+    /// The day `year-month-day` of this calendar as a proleptic Gregorian
+    /// date, or `None` if this calendar has no such day.
+    pub fn date(self, year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        let julian = match self {
+            Reform::Y1752 => {
+                if (year, month) == (1752, 9) && (3..=13).contains(&day) {
+                    return None;
+                }
+                (year, month, day) < (1752, 9, 3)
+            }
+            Reform::Iso => false,
+            Reform::None => true,
+        };
+        if julian {
+            julian_date(year, month, day)
+        } else {
+            NaiveDate::from_ymd_opt(year, month, day)
+        }
+    }
+
+    /// The days of a month in this calendar with their Gregorian dates.
+    pub fn month_days(self, year: i32, month: u32) -> Vec<(u32, NaiveDate)> {
+        (1..=31)
+            .filter_map(|day| self.date(year, month, day).map(|date| (day, date)))
+            .collect()
+    }
+}
+
+/// Convert a Julian calendar date by way of its Julian day number.
+fn julian_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let leap = month == 2 && year % 4 == 0;
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > DAYS_IN_MONTH[month as usize - 1] + leap as u32
+    {
+        return None;
+    }
+    let a = (14 - month as i32) / 12;
+    let y = year + 4800 - a;
+    let m = month as i32 + 12 * a - 3;
+    let jdn = day as i32 + (153 * m + 2) / 5 + 365 * y + y / 4 - 32083;
+    // Day 1 of the common era, 0001-01-01, is Julian day 1721426
+    NaiveDate::from_num_days_from_ce_opt(jdn - 1721425)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reform;
+    use chrono::NaiveDate;
+
+    fn ymd(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(Reform::Y1752.date(1752, 9, 2), ymd(1752, 9, 13));
+        assert_eq!(Reform::Y1752.date(1752, 9, 3), None);
+        assert_eq!(Reform::Y1752.date(1752, 9, 13), None);
+        assert_eq!(Reform::Y1752.date(1752, 9, 14), ymd(1752, 9, 14));
+        assert_eq!(Reform::Y1752.date(1700, 2, 29), ymd(1700, 3, 11));
+        assert_eq!(Reform::Iso.date(1700, 2, 29), None);
+        assert_eq!(Reform::Iso.date(1752, 9, 5), ymd(1752, 9, 5));
+        assert_eq!(Reform::None.date(2020, 1, 1), ymd(2020, 1, 14));
+        assert_eq!(Reform::None.date(2021, 4, 31), None);
+    }
+
+    #[test]
+    fn test_month_days() {
+        let days: Vec<_> = Reform::Y1752
+            .month_days(1752, 9)
+            .iter()
+            .map(|(day, _)| *day)
+            .collect();
+        assert_eq!(days, [1, 2].into_iter().chain(14..=30).collect::<Vec<_>>());
+        assert_eq!(Reform::None.month_days(1900, 2).len(), 29);
+        assert_eq!(Reform::Iso.month_days(1900, 2).len(), 28);
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{Error, Result};
+use chrono::NaiveDate;
+
+/// A dated entry from an events file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Event {
+    pub date: NaiveDate,
+    pub description: String,
+}
+
+/// Parse an events file, either iCalendar or one `YYYY-MM-DD description`
+/// per line. Blank lines and lines starting with `#` are skipped.
+pub fn parse_events(text: &str) -> Result<Vec<Event>> {
+    if text.trim_start().starts_with("BEGIN:VCALENDAR") {
+        parse_ical(text)
+    } else {
+        parse_lines(text)
+    }
+}
+
+fn parse_lines(text: &str) -> Result<Vec<Event>> {
+    let mut events = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (date, description) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| Error::msg(format!("line {}: invalid date \"{}\"", i + 1, date)))?;
+        events.push(Event {
+            date,
+            description: description.trim().to_string(),
+        });
+    }
+    Ok(events)
+}
+
+/// Read the DTSTART and SUMMARY of each VEVENT. Recurrence rules are not
+/// expanded, so an event only shows on its first date.
+fn parse_ical(text: &str) -> Result<Vec<Event>> {
+    let mut events = vec![];
+    let mut current: Option<(Option<NaiveDate>, String)> = None;
+    for line in unfold(text) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap_or_default();
+        match (name, current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some((None, String::new())),
+            ("END", Some(_)) if value == "VEVENT" => {
+                let (date, description) = current.take().unwrap();
+                let date = date.ok_or_else(|| Error::msg("event without DTSTART"))?;
+                events.push(Event { date, description });
+            }
+            ("DTSTART", Some((date, _))) => {
+                *date = Some(
+                    value
+                        .get(..8)
+                        .and_then(|ymd| NaiveDate::parse_from_str(ymd, "%Y%m%d").ok())
+                        .ok_or_else(|| Error::msg(format!("invalid DTSTART \"{}\"", value)))?,
+                );
+            }
+            ("SUMMARY", Some((_, description))) => *description = unescape(value),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+/// Join folded iCalendar lines, which continue with a leading space or tab.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push(' '),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_events, Event};
+    use chrono::NaiveDate;
+
+    fn event(y: i32, m: u32, d: u32, description: &str) -> Event {
+        Event {
+            date: NaiveDate::from_ymd_opt(y, m, d).unwrap(),
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let text = "# holidays\n2020-12-25 Christmas Day\n\n2020-12-31\tNew Year's Eve\n";
+        assert_eq!(
+            parse_events(text).unwrap(),
+            vec![
+                event(2020, 12, 25, "Christmas Day"),
+                event(2020, 12, 31, "New Year's Eve"),
+            ]
+        );
+
+        let res = parse_events("2020-12-25 ok\n2020-13-01 bad\n");
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "line 2: invalid date \"2020-13-01\""
+        );
+    }
+
+    #[test]
+    fn test_parse_ical() {
+        let text = "BEGIN:VCALENDAR\r\n\
+                    BEGIN:VEVENT\r\n\
+                    DTSTART;VALUE=DATE:20201225\r\n\
+                    SUMMARY:Christmas\\, at\r\n  home\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    SUMMARY:Call\r\n\
+                    DTSTART:20201201T090000Z\r\n\
+                    END:VEVENT\r\n\
+                    END:VCALENDAR\r\n";
+        assert_eq!(
+            parse_events(text).unwrap(),
+            vec![
+                event(2020, 12, 25, "Christmas, at home"),
+                event(2020, 12, 1, "Call"),
+            ]
+        );
+
+        let res = parse_events("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VEVENT\n");
+        assert_eq!(res.unwrap_err().to_string(), "event without DTSTART");
+    }
+}
@@ -0,0 +1,833 @@
+mod calendar;
+mod events;
+mod moon;
+
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{self, IsTerminal},
+    str::FromStr,
+};
+
+use ansi_term::{Colour, Style};
+use anyhow::{Error, Result};
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
+
+use crate::events::{parse_events, Event};
+
+pub use crate::calendar::Reform;
+
+const VALID_MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// When to highlight today.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColorWhen {
+    /// When stdout is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+/// The first positional argument: a year, a `YEAR-MONTH`, or the month of
+/// `MONTH YEAR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateArg {
+    Year(i32),
+    YearMonth(i32, u32),
+    Month(u32),
+}
+
+#[derive(Parser)]
+#[command(override_usage = "calr [OPTIONS] [YEAR | YEAR-MONTH | MONTH YEAR]")]
+pub struct Config {
+    /// Year (1-9999), YEAR-MONTH, or the month when followed by a year
+    #[arg(value_name = "YEAR", value_parser = parse_date_arg)]
+    year: Option<DateArg>,
+
+    /// Year (1-9999) after a month
+    #[arg(
+        value_name = "YEAR",
+        value_parser(clap::value_parser!(i32).range(1..=9999)),
+        conflicts_with("month")
+    )]
+    then_year: Option<i32>,
+
+    /// Month name or number (1-12)
+    #[arg(short = 'm', value_name = "MONTH", conflicts_with("show_current_year"))]
+    month: Option<String>,
+
+    /// Show whole current year
+    #[arg(short = 'y', long = "year", conflicts_with_all(["year", "month"]) )]
+    show_current_year: bool,
+
+    /// Show the previous, current and next month
+    #[arg(short = '3', conflicts_with_all(["show_current_year", "after", "before"]))]
+    three_months: bool,
+
+    /// Show NUM months after the month
+    #[arg(short = 'A', value_name = "NUM", conflicts_with("show_current_year"))]
+    after: Option<u32>,
+
+    /// Show NUM months before the month
+    #[arg(short = 'B', value_name = "NUM", conflicts_with("show_current_year"))]
+    before: Option<u32>,
+
+    /// Highlight today: auto, always or never
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value = "auto",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    color: ColorWhen,
+
+    /// Style of today, as comma-separated attributes (bold, dimmed, italic,
+    /// underline, blink, reverse, hidden, strikethrough) and colors (fg=COLOR,
+    /// bg=COLOR, where COLOR is a name, 0-255 or #rrggbb)
+    #[arg(
+        long = "today-style",
+        value_name = "STYLE",
+        default_value = "reverse",
+        value_parser = parse_style
+    )]
+    today_style: Style,
+
+    /// Switch from the Julian to the Gregorian calendar in 1752, or use
+    /// only the Gregorian (iso) or Julian (none) calendar
+    #[arg(
+        long = "reform",
+        value_name = "WHEN",
+        value_enum,
+        default_value = "1752"
+    )]
+    reform: Reform,
+
+    /// Show ISO-8601 week numbers
+    #[arg(short = 'w', long = "week-numbers")]
+    week_numbers: bool,
+
+    /// Highlight the dates in FILE, given as iCalendar or `YYYY-MM-DD text` lines
+    #[arg(long = "events", value_name = "FILE")]
+    events: Option<String>,
+
+    /// Mark new (*) and full (o) moons, by UTC date, and list them below
+    /// the calendar
+    #[arg(long = "moon")]
+    moon: bool,
+
+    /// List the events of the shown months below the calendar
+    #[arg(long = "list-events", requires("events"))]
+    list_events: bool,
+
+    /// Show today's day of the year, the days left and the ISO week below
+    /// the calendar
+    #[arg(long = "footer")]
+    footer: bool,
+
+    /// Leave out blank week rows at the end of months
+    #[arg(long = "compact")]
+    compact: bool,
+
+    /// Show NUM months starting with the month
+    #[arg(
+        short = 'n',
+        long = "months",
+        value_name = "NUM",
+        value_parser(clap::value_parser!(u32).range(1..)),
+        conflicts_with_all(["show_current_year", "three_months", "after", "before"])
+    )]
+    months: Option<u32>,
+}
+
+fn parse_int<T: FromStr>(val: &str) -> Result<T> {
+    val.parse::<T>()
+        .map_err(|_| Error::msg(format!("Invalid integer \"{}\"", val)))
+}
+
+fn parse_year(val: &str) -> Result<i32> {
+    let year = val.parse::<i32>().map_err(|e| Error::msg(e.to_string()))?;
+    if (1..=9999).contains(&year) {
+        Ok(year)
+    } else {
+        Err(Error::msg(format!("{} is not in 1..=9999", year)))
+    }
+}
+
+/// Parse a year, a `YEAR-MONTH` such as `2025-03`, or else a month name.
+fn parse_date_arg(val: &str) -> Result<DateArg> {
+    if let Some((year, month)) = val.split_once('-') {
+        let month = parse_int::<u32>(month)
+            .ok()
+            .filter(|month| (1..=12).contains(month))
+            .ok_or_else(|| Error::msg(format!("invalid month in \"{}\"", val)))?;
+        return Ok(DateArg::YearMonth(parse_year(year)?, month));
+    }
+    parse_year(val).map(DateArg::Year).or_else(|e| {
+        if val.parse::<i32>().is_ok() {
+            return Err(e);
+        }
+        parse_month(val).map(DateArg::Month).map_err(|_| e)
+    })
+}
+
+/// Parse a month number (1-12) or a unique prefix of its English name,
+/// ignoring case.
+pub fn parse_month(month: &str) -> Result<u32> {
+    let month_range = 1..=12;
+    match parse_int::<u32>(month) {
+        Ok(month) => {
+            if month_range.contains(&month) {
+                Ok(month)
+            } else {
+                Err(Error::msg(format!(
+                    "month \"{}\" not in the range {} through {}",
+                    month,
+                    month_range.start(),
+                    month_range.end()
+                )))
+            }
+        }
+        _ => {
+            let mut candidate = None;
+            for (i, valid_name) in VALID_MONTH_NAMES.iter().enumerate() {
+                if valid_name
+                    .to_lowercase()
+                    .starts_with::<&str>(month.to_lowercase().as_ref())
+                {
+                    if candidate.is_some() {
+                        candidate = None;
+                        break;
+                    }
+                    candidate = Some(i as u32 + 1);
+                }
+            }
+            candidate.ok_or(Error::msg(format!("Invalid month \"{}\"", month)))
+        }
+    }
+}
+
+/// The dates to highlight: today in its own style, events underlined. With
+/// `moon`, new moons are marked `*` and full moons `o` after the day.
+#[derive(Default)]
+pub struct Highlights {
+    pub today: Option<NaiveDate>,
+    pub today_style: Style,
+    pub events: HashSet<NaiveDate>,
+    pub moon: bool,
+}
+
+impl Highlights {
+    fn style(&self, date: NaiveDate) -> Option<Style> {
+        let mut style = Style::new();
+        if self.today == Some(date) {
+            style = self.today_style;
+        }
+        if self.events.contains(&date) {
+            style = style.underline();
+        }
+        (!style.is_plain()).then_some(style)
+    }
+}
+
+fn parse_colour(colour: &str) -> Result<Colour> {
+    let invalid = || Error::msg(format!("invalid color \"{}\"", colour));
+    Ok(match colour.to_lowercase().as_str() {
+        "black" => Colour::Black,
+        "red" => Colour::Red,
+        "green" => Colour::Green,
+        "yellow" => Colour::Yellow,
+        "blue" => Colour::Blue,
+        "purple" | "magenta" => Colour::Purple,
+        "cyan" => Colour::Cyan,
+        "white" => Colour::White,
+        hex if hex.starts_with('#') => {
+            let rgb = u32::from_str_radix(&hex[1..], 16)
+                .ok()
+                .filter(|_| hex.len() == 7)
+                .ok_or_else(invalid)?;
+            Colour::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+        }
+        number => Colour::Fixed(number.parse().map_err(|_| invalid())?),
+    })
+}
+
+/// Parse a style such as `bold,fg=red,bg=white` into an ansi_term style.
+fn parse_style(spec: &str) -> Result<Style> {
+    let mut style = Style::new();
+    for item in spec.split(',').map(str::trim) {
+        style = match item.split_once('=') {
+            Some(("fg", colour)) => style.fg(parse_colour(colour)?),
+            Some(("bg", colour)) => style.on(parse_colour(colour)?),
+            Some(_) => return Err(Error::msg(format!("invalid style \"{}\"", item))),
+            None => match item {
+                "bold" => style.bold(),
+                "dimmed" | "dim" => style.dimmed(),
+                "italic" => style.italic(),
+                "underline" => style.underline(),
+                "blink" => style.blink(),
+                "reverse" => style.reverse(),
+                "hidden" => style.hidden(),
+                "strikethrough" => style.strikethrough(),
+                _ => return Err(Error::msg(format!("invalid style \"{}\"", item))),
+            },
+        };
+    }
+    Ok(style)
+}
+
+/// Format one month as 8 lines: its title, the weekday names and 6 weeks.
+/// With `week_numbers`, each week starts with the ISO-8601 number of the week
+/// its Monday is in.
+pub fn format_month(
+    year: i32,
+    month: u32,
+    print_year: bool,
+    week_numbers: bool,
+    reform: Reform,
+    highlights: &Highlights,
+) -> Vec<String> {
+    let width = 20;
+    let last_space = "  ";
+    let week_space = if week_numbers { "   " } else { "" };
+    let mut format_month = vec![];
+    format_month.push(format!(
+        "{week_space}{:^width$}  ",
+        format!(
+            "{}{}",
+            VALID_MONTH_NAMES[month as usize - 1],
+            if print_year {
+                format!(" {}", year)
+            } else {
+                "".to_string()
+            }
+        )
+    ));
+    format_month.push(format!(
+        "{}{:<width$}{}",
+        if week_numbers { "Wk " } else { "" },
+        "Su Mo Tu We Th Fr Sa",
+        last_space
+    ));
+
+    // Days missing from the month, as in September 1752, leave no gap
+    let days = reform.month_days(year, month);
+    let offset = days[0].1.weekday().num_days_from_sunday() as usize;
+    let first_sunday = days[0].1 - Days::new(offset as u64);
+    let moons = if highlights.moon {
+        moon::phases(days[0].1, days[days.len() - 1].1)
+    } else {
+        vec![]
+    };
+    let mut cells = vec![None; offset];
+    cells.extend(days.into_iter().map(Some));
+    let num_weeks_in_month = 6;
+    cells.resize(7 * num_weeks_in_month, None);
+    for (week, sunday) in cells.chunks(7).zip(first_sunday.iter_weeks()) {
+        let mut format_days_in_week = vec![];
+        let week_number = if !week_numbers {
+            String::new()
+        } else if week.iter().any(Option::is_some) {
+            format!("{:>2} ", sunday.succ_opt().unwrap().iso_week().week())
+        } else {
+            week_space.to_owned()
+        };
+        for cell in week {
+            if let Some((day, date)) = cell {
+                let format_day = format!("{:>2}", day);
+                let mark = moons
+                    .iter()
+                    .find(|(moon, _)| moon == date)
+                    .map_or(' ', |(_, phase)| phase.mark());
+                format_days_in_week.push(match highlights.style(*date) {
+                    Some(style) => format!("{}{}", style.paint(format_day), mark),
+                    None => format!("{}{}", format_day, mark),
+                });
+            } else {
+                format_days_in_week.push("   ".to_owned());
+            }
+        }
+        format_month.push(format!("{}{} ", week_number, format_days_in_week.concat()));
+    }
+    format_month
+}
+
+/// Lay formatted months out side by side, `columns` to a row, with an empty
+/// line between rows.
+fn join_months(months: &[Vec<String>], columns: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for (i, row) in months.chunks(columns).enumerate() {
+        if i > 0 {
+            lines.push(String::new());
+        }
+        for line in 0..row[0].len() {
+            lines.push(row.iter().map(|month| &month[line]).join(""));
+        }
+    }
+    lines
+}
+
+/// Format a whole year: the year centered over its months, three to a row.
+pub fn format_year(
+    year: i32,
+    week_numbers: bool,
+    reform: Reform,
+    highlights: &Highlights,
+) -> Vec<String> {
+    let months: Vec<_> = (1..=12)
+        .map(|month| format_month(year, month, false, week_numbers, reform, highlights))
+        .collect();
+    let mut lines = vec![format!(
+        "{:>width$}",
+        year,
+        width = if week_numbers { 36 } else { 32 }
+    )];
+    lines.extend(join_months(&months, 3));
+    lines
+}
+
+/// Print formatted lines. With `compact`, blank week rows at the end of a
+/// row of months are left out, keeping one empty line between rows.
+fn print_lines(lines: &[String], compact: bool) {
+    let blank = |line: &String| line.trim().is_empty();
+    for (i, line) in lines.iter().enumerate() {
+        if compact && blank(line) && lines.get(i + 1).is_none_or(blank) {
+            continue;
+        }
+        println!("{}", line);
+    }
+}
+
+/// Show `before` months before the month of `first`, that month itself and
+/// `after` months after it, each titled with its year. Returns the first
+/// days of the first and last month shown.
+fn show_months(
+    first: NaiveDate,
+    before: u32,
+    after: u32,
+    week_numbers: bool,
+    reform: Reform,
+    highlights: &Highlights,
+    compact: bool,
+) -> Result<(NaiveDate, NaiveDate)> {
+    let dates = (0..=before.saturating_add(after))
+        .map(|i| {
+            first
+                .checked_sub_months(Months::new(before))
+                .and_then(|start| start.checked_add_months(Months::new(i)))
+                .filter(|date| (1..=9999).contains(&date.year()))
+                .ok_or_else(|| Error::msg("months must be within the years 1 through 9999"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let months: Vec<_> = dates
+        .iter()
+        .map(|date| {
+            format_month(
+                date.year(),
+                date.month(),
+                true,
+                week_numbers,
+                reform,
+                highlights,
+            )
+        })
+        .collect();
+    print_lines(&join_months(&months, 3), compact);
+    Ok((dates[0], dates[dates.len() - 1]))
+}
+
+fn read_events(path: &str) -> Result<Vec<Event>> {
+    fs::read_to_string(path)
+        .map_err(Error::from)
+        .and_then(|text| parse_events(&text))
+        .map_err(|e| Error::msg(format!("{}: {}", path, e)))
+}
+
+/// Summarize where `today` falls in its year, such as
+/// `Day 289 of 365, 76 days left, week 2026-W42`.
+pub fn format_footer(today: NaiveDate) -> String {
+    let days_in_year = if today.leap_year() { 366 } else { 365 };
+    let week = today.iso_week();
+    format!(
+        "Day {} of {}, {} days left, week {}-W{:02}",
+        today.ordinal(),
+        days_in_year,
+        days_in_year - today.ordinal(),
+        week.year(),
+        week.week()
+    )
+}
+
+/// List the events from `first` through `last` by date, after an empty line.
+fn print_events(events: &[Event], first: NaiveDate, last: NaiveDate) {
+    let shown: Vec<_> = events
+        .iter()
+        .filter(|event| (first..=last).contains(&event.date))
+        .sorted_by_key(|event| event.date)
+        .collect();
+    if !shown.is_empty() {
+        println!();
+    }
+    for event in shown {
+        println!("{}  {}", event.date, event.description);
+    }
+}
+
+/// Whether to highlight, given `--color` and the environment.
+fn use_color(when: ColorWhen) -> bool {
+    match when {
+        ColorWhen::Always => true,
+        ColorWhen::Never => false,
+        ColorWhen::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|val| val.is_empty())
+        }
+    }
+}
+
+pub fn get_args() -> Result<Config> {
+    Ok(Config::parse())
+}
+
+pub fn run(config: Config) -> Result<()> {
+    let today = Local::now().date_naive();
+    let events = match &config.events {
+        Some(path) => read_events(path)?,
+        None => vec![],
+    };
+    let highlights = if use_color(config.color) {
+        Highlights {
+            today: Some(today),
+            today_style: config.today_style,
+            events: events.iter().map(|event| event.date).collect(),
+            moon: config.moon,
+        }
+    } else {
+        Highlights {
+            moon: config.moon,
+            ..Highlights::default()
+        }
+    };
+    let (first, last) = if config.show_current_year {
+        print_lines(
+            &format_year(
+                today.year(),
+                config.week_numbers,
+                config.reform,
+                &highlights,
+            ),
+            config.compact,
+        );
+        (
+            NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(today.year(), 12, 1).unwrap(),
+        )
+    } else {
+        let month = config
+            .month
+            .as_ref()
+            .map(|month| parse_month(month))
+            .transpose()?;
+        let (year, month) = match (config.year, config.then_year) {
+            (Some(DateArg::Year(month)), Some(year)) => {
+                (Some(year), Some(parse_month(&month.to_string())?))
+            }
+            (Some(DateArg::Month(month)), Some(year)) => (Some(year), Some(month)),
+            (Some(DateArg::YearMonth(..)), Some(_)) => {
+                return Err(Error::msg("a year cannot follow YEAR-MONTH"))
+            }
+            (Some(DateArg::Year(year)), None) => (Some(year), month),
+            (Some(DateArg::YearMonth(..) | DateArg::Month(_)), None) if month.is_some() => {
+                return Err(Error::msg("-m cannot be used with a month argument"))
+            }
+            (Some(DateArg::YearMonth(year, month)), None) => (Some(year), Some(month)),
+            (Some(DateArg::Month(month)), None) => (None, Some(month)),
+            (None, _) => (None, month),
+        };
+        let (before, after) = if config.three_months {
+            (Some(1), Some(1))
+        } else if let Some(months) = config.months {
+            (None, Some(months - 1))
+        } else {
+            (config.before, config.after)
+        };
+        match (year, month) {
+            (_, _) if before.is_some() || after.is_some() => {
+                let year = year.unwrap_or(today.year());
+                let month = month.unwrap_or(today.month());
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                show_months(
+                    first,
+                    before.unwrap_or(0),
+                    after.unwrap_or(0),
+                    config.week_numbers,
+                    config.reform,
+                    &highlights,
+                    config.compact,
+                )?
+            }
+            (Some(year), None) => {
+                print_lines(
+                    &format_year(year, config.week_numbers, config.reform, &highlights),
+                    config.compact,
+                );
+                (
+                    NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+                    NaiveDate::from_ymd_opt(year, 12, 1).unwrap(),
+                )
+            }
+            _ => {
+                let year = year.unwrap_or(today.year());
+                let month = month.unwrap_or(today.month());
+                print_lines(
+                    &format_month(
+                        year,
+                        month,
+                        true,
+                        config.week_numbers,
+                        config.reform,
+                        &highlights,
+                    ),
+                    config.compact,
+                );
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                (first, first)
+            }
+        }
+    };
+    if config.footer {
+        println!();
+        println!("{}", format_footer(today));
+    }
+    let last = last
+        .checked_add_months(Months::new(1))
+        .unwrap()
+        .pred_opt()
+        .unwrap();
+    let mut listed = vec![];
+    if config.list_events {
+        listed.extend(events);
+    }
+    if config.moon {
+        listed.extend(
+            moon::phases(first, last)
+                .into_iter()
+                .map(|(date, phase)| Event {
+                    date,
+                    description: phase.name().to_string(),
+                }),
+        );
+    }
+    print_events(&listed, first, last);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_int() {
+        let res = parse_int::<usize>("1");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 1usize);
+
+        let res = parse_int::<i32>("-1");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), -1i32);
+
+        let res = parse_int::<i64>("foo");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "Invalid integer \"foo\"");
+    }
+
+    #[test]
+    fn test_parse_date_arg() {
+        assert_eq!(parse_date_arg("2025").unwrap(), DateArg::Year(2025));
+        assert_eq!(parse_date_arg("3").unwrap(), DateArg::Year(3));
+        assert_eq!(
+            parse_date_arg("2025-03").unwrap(),
+            DateArg::YearMonth(2025, 3)
+        );
+        assert_eq!(parse_date_arg("mar").unwrap(), DateArg::Month(3));
+
+        let res = parse_date_arg("0");
+        assert_eq!(res.unwrap_err().to_string(), "0 is not in 1..=9999");
+
+        let res = parse_date_arg("foo");
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "invalid digit found in string"
+        );
+
+        let res = parse_date_arg("2025-13");
+        assert_eq!(res.unwrap_err().to_string(), "invalid month in \"2025-13\"");
+
+        let res = parse_date_arg("10000-01");
+        assert_eq!(res.unwrap_err().to_string(), "10000 is not in 1..=9999");
+    }
+
+    #[test]
+    fn test_parse_month() {
+        let res = parse_month("1");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 1u32);
+
+        let res = parse_month("12");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 12u32);
+
+        let res = parse_month("jan");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 1u32);
+
+        let res = parse_month("0");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "month \"0\" not in the range 1 through 12"
+        );
+
+        let res = parse_month("13");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "month \"13\" not in the range 1 through 12"
+        );
+
+        let res = parse_month("foo");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "Invalid month \"foo\"");
+    }
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(parse_style("reverse").unwrap(), Style::new().reverse());
+        assert_eq!(
+            parse_style("bold, underline").unwrap(),
+            Style::new().bold().underline()
+        );
+        assert_eq!(
+            parse_style("fg=red,bg=white").unwrap(),
+            Colour::Red.on(Colour::White)
+        );
+        assert_eq!(
+            parse_style("fg=208,bg=#0080ff").unwrap(),
+            Colour::Fixed(208).on(Colour::RGB(0, 128, 255))
+        );
+
+        let res = parse_style("bold,shiny");
+        assert_eq!(res.unwrap_err().to_string(), "invalid style \"shiny\"");
+
+        let res = parse_style("fg=pink");
+        assert_eq!(res.unwrap_err().to_string(), "invalid color \"pink\"");
+
+        let res = parse_style("fg=#fff");
+        assert_eq!(res.unwrap_err().to_string(), "invalid color \"#fff\"");
+    }
+
+    #[test]
+    fn test_format_footer() {
+        let footer = |y, m, d| format_footer(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        assert_eq!(
+            footer(2026, 10, 16),
+            "Day 289 of 365, 76 days left, week 2026-W42"
+        );
+        assert_eq!(
+            footer(2021, 1, 1),
+            "Day 1 of 365, 364 days left, week 2020-W53"
+        );
+        assert_eq!(
+            footer(2020, 12, 31),
+            "Day 366 of 366, 0 days left, week 2020-W53"
+        );
+    }
+
+    #[test]
+    fn test_format_month() {
+        let leap_february = vec![
+            "   February 2020      ",
+            "Su Mo Tu We Th Fr Sa  ",
+            "                   1  ",
+            " 2  3  4  5  6  7  8  ",
+            " 9 10 11 12 13 14 15  ",
+            "16 17 18 19 20 21 22  ",
+            "23 24 25 26 27 28 29  ",
+            "                      ",
+        ];
+        assert_eq!(
+            format_month(2020, 2, true, false, Reform::Iso, &Highlights::default()),
+            leap_february
+        );
+
+        let may = vec![
+            "        May           ",
+            "Su Mo Tu We Th Fr Sa  ",
+            "                1  2  ",
+            " 3  4  5  6  7  8  9  ",
+            "10 11 12 13 14 15 16  ",
+            "17 18 19 20 21 22 23  ",
+            "24 25 26 27 28 29 30  ",
+            "31                    ",
+        ];
+        assert_eq!(
+            format_month(2020, 5, false, false, Reform::Iso, &Highlights::default()),
+            may
+        );
+
+        let april_hl = vec![
+            "     April 2021       ",
+            "Su Mo Tu We Th Fr Sa  ",
+            "             1  2  3  ",
+            " 4  5  6 \u{1b}[4;7m 7\u{1b}[0m  8  9 10  ",
+            "11 12 13 14 15 16 17  ",
+            "18 19 20 21 22 23 24  ",
+            "25 26 27 28 29 \u{1b}[4m30\u{1b}[0m     ",
+            "                      ",
+        ];
+        let highlights = Highlights {
+            today: NaiveDate::from_ymd_opt(2021, 4, 7),
+            today_style: Style::new().reverse(),
+            events: HashSet::from([
+                NaiveDate::from_ymd_opt(2021, 4, 7).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 4, 30).unwrap(),
+            ]),
+            moon: false,
+        };
+        assert_eq!(
+            format_month(2021, 4, true, false, Reform::Iso, &highlights),
+            april_hl
+        );
+
+        let january_weeks = vec![
+            "       January 2021      ",
+            "Wk Su Mo Tu We Th Fr Sa  ",
+            "53                 1  2  ",
+            " 1  3  4  5  6  7  8  9  ",
+            " 2 10 11 12 13 14 15 16  ",
+            " 3 17 18 19 20 21 22 23  ",
+            " 4 24 25 26 27 28 29 30  ",
+            " 5 31                    ",
+        ];
+        assert_eq!(
+            format_month(2021, 1, true, true, Reform::Iso, &Highlights::default()),
+            january_weeks
+        );
+    }
+}
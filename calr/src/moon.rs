@@ -0,0 +1,123 @@
+use std::f64::consts::PI;
+
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    New,
+    Full,
+}
+
+impl Phase {
+    /// The character marking the phase in the grid.
+    pub fn mark(self) -> char {
+        match self {
+            Phase::New => '*',
+            Phase::Full => 'o',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::New => "New moon",
+            Phase::Full => "Full moon",
+        }
+    }
+}
+
+const SYNODIC_MONTH: f64 = 29.530588861;
+
+/// Julian ephemeris day of the new moon of 6 January 2000, lunation 0
+const NEW_MOON_2000: f64 = 2451550.09766;
+
+/// The new and full moons from `first` through `last`, by UTC date.
+pub fn phases(first: NaiveDate, last: NaiveDate) -> Vec<(NaiveDate, Phase)> {
+    let start = ((julian_day(first) - NEW_MOON_2000) / SYNODIC_MONTH).floor() - 1.0;
+    let mut phases = vec![];
+    for k in (0..).map(|i| start + i as f64 / 2.0) {
+        let phase = if k.fract() == 0.0 {
+            Phase::New
+        } else {
+            Phase::Full
+        };
+        let Some(date) = date(phase_day(k, phase)) else {
+            break;
+        };
+        if date > last {
+            break;
+        }
+        if date >= first {
+            phases.push((date, phase));
+        }
+    }
+    phases
+}
+
+fn julian_day(date: NaiveDate) -> f64 {
+    date.num_days_from_ce() as f64 + 1721424.5
+}
+
+fn date(julian_day: f64) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt((julian_day - 1721424.5).floor() as i32)
+}
+
+/// The instant of a new (integer `k`) or full (`k` + 0.5) moon, after Jean
+/// Meeus, Astronomical Algorithms, ch. 49, keeping the larger periodic terms.
+/// This is good to a few minutes, which only matters near midnight.
+fn phase_day(k: f64, phase: Phase) -> f64 {
+    let t = k / 1236.85;
+    let jde = NEW_MOON_2000 + SYNODIC_MONTH * k + 0.00015437 * t * t;
+    let e = 1.0 - 0.002516 * t;
+    let deg = |angle: f64| angle * PI / 180.0;
+    let m = deg(2.5534 + 29.10535670 * k);
+    let m1 = deg(201.5643 + 385.81693528 * k);
+    let f = deg(160.7108 + 390.67050284 * k);
+    let (c1, c2, c3, c4, c5) = match phase {
+        Phase::New => (-0.40720, 0.17241, 0.01608, 0.01039, 0.00739),
+        Phase::Full => (-0.40614, 0.17302, 0.01614, 0.01043, 0.00734),
+    };
+    let correction = c1 * m1.sin()
+        + c2 * e * m.sin()
+        + c3 * (2.0 * m1).sin()
+        + c4 * (2.0 * f).sin()
+        + c5 * e * (m1 - m).sin()
+        - 0.00514 * e * (m1 + m).sin()
+        + 0.00208 * e * e * (2.0 * m).sin()
+        - 0.00111 * (m1 - 2.0 * f).sin()
+        - 0.00057 * (m1 + 2.0 * f).sin()
+        + 0.00056 * e * (2.0 * m1 + m).sin()
+        - 0.00042 * (3.0 * m1).sin();
+    jde + correction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{phases, Phase};
+    use chrono::NaiveDate;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_phases() {
+        assert_eq!(
+            phases(ymd(2020, 12, 1), ymd(2021, 1, 31)),
+            vec![
+                (ymd(2020, 12, 14), Phase::New),
+                (ymd(2020, 12, 30), Phase::Full),
+                (ymd(2021, 1, 13), Phase::New),
+                (ymd(2021, 1, 28), Phase::Full),
+            ]
+        );
+        // Two full moons in one month
+        assert_eq!(
+            phases(ymd(2023, 8, 1), ymd(2023, 8, 31)),
+            vec![
+                (ymd(2023, 8, 1), Phase::Full),
+                (ymd(2023, 8, 16), Phase::New),
+                (ymd(2023, 8, 31), Phase::Full),
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use assert_cmd::Command;
+use calr::{format_month, format_year, parse_month, Highlights, Reform};
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use std::fs;
@@ -226,3 +227,422 @@ fn year() -> Result<()> {
     assert_eq!(lines.len(), 37);
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn test_3_1_2020() -> Result<()> {
+    run(&["-3", "-m", "1", "2020"], "tests/expected/3-1-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_b2_a3_12_2020() -> Result<()> {
+    run(
+        &["-B", "2", "-A", "3", "-m", "12", "2020"],
+        "tests/expected/B2-A3-12-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn three_months() -> Result<()> {
+    let cmd = Command::cargo_bin(PRG)?.arg("-3").assert().success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.split('\n').collect();
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[0].len(), 66);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_months_after_9999() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["-A", "1", "-m", "12", "9999"])
+        .output()
+        .expect("fail");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).expect("invalid UTF-8");
+    assert_eq!(
+        stderr.trim(),
+        "months must be within the years 1 through 9999"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_n4_nov_2020() -> Result<()> {
+    run(
+        &["-m", "Nov", "-n", "4", "2020"],
+        "tests/expected/n4-11-2020.txt",
+    )?;
+    run(
+        &["2020", "--months", "4", "-m", "11"],
+        "tests/expected/n4-11-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_n1_4_2020() -> Result<()> {
+    run(&["-n", "1", "-m", "4", "2020"], "tests/expected/4-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_months_0() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--months <NUM>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_w_1_2021() -> Result<()> {
+    run(&["-w", "-m", "1", "2021"], "tests/expected/w-1-2021.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_w_2020() -> Result<()> {
+    run(&["--week-numbers", "2020"], "tests/expected/w-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always_highlights_today() -> Result<()> {
+    for args in [&["--color=always"][..], &["--color"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .env("NO_COLOR", "1")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\u{1b}[7m"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_color_when_piped() -> Result<()> {
+    for args in [&[][..], &["--color=auto"], &["--color=never"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\u{1b}").not());
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_color() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("--color=sometimes")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value 'sometimes' for '--color[=<WHEN>]'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_events_12_2020() -> Result<()> {
+    run(
+        &[
+            "--events",
+            "tests/inputs/events.txt",
+            "--list-events",
+            "-m",
+            "12",
+            "2020",
+        ],
+        "tests/expected/events-12-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_events_ics_12_2020() -> Result<()> {
+    run(
+        &[
+            "--events",
+            "tests/inputs/events.ics",
+            "--list-events",
+            "-m",
+            "12",
+            "2020",
+        ],
+        "tests/expected/events-ics-12-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn events_without_list() -> Result<()> {
+    run(
+        &["--events", "tests/inputs/events.txt", "-m", "12", "2020"],
+        "tests/expected/12-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn events_underlined() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", "--events", "tests/inputs/events.txt"])
+        .args(["-m", "12", "2020"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "20 21 22 23 \u{1b}[4m24\u{1b}[0m \u{1b}[4m25\u{1b}[0m 26",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_events() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--events", "tests/inputs/bad-events.txt"])
+        .assert()
+        .failure()
+        .stderr("tests/inputs/bad-events.txt: line 2: invalid date \"December\"\n");
+    Command::cargo_bin(PRG)?
+        .args(["--events", "tests/inputs/missing.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("tests/inputs/missing.txt: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_9_1752() -> Result<()> {
+    run(&["-m", "9", "1752"], "tests/expected/9-1752.txt")?;
+    run(
+        &["--reform", "1752", "-m", "9", "1752"],
+        "tests/expected/9-1752.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_reform_iso_9_1752() -> Result<()> {
+    run(
+        &["--reform", "iso", "-m", "9", "1752"],
+        "tests/expected/iso-9-1752.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_reform_none_1_2021() -> Result<()> {
+    run(
+        &["--reform", "none", "-m", "1", "2021"],
+        "tests/expected/julian-1-2021.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_reform() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--reform", "1582"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '1582' for '--reform <WHEN>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn today_style() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", "--today-style", "bold,fg=red"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}[1;31m"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_today_style() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--today-style", "bold,fg=pink"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value 'bold,fg=pink' for '--today-style <STYLE>': \
+                invalid color \"pink\"",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn lib_format_year() -> Result<()> {
+    let plain = Highlights::default();
+    for (week_numbers, expected_file) in [
+        (false, "tests/expected/2020.txt"),
+        (true, "tests/expected/w-2020.txt"),
+    ] {
+        let lines = format_year(2020, week_numbers, Reform::Iso, &plain);
+        assert_eq!(lines.join("\n") + "\n", fs::read_to_string(expected_file)?);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn lib_format_month() -> Result<()> {
+    let month = parse_month("sep")?;
+    let lines = format_month(
+        1752,
+        month,
+        true,
+        false,
+        Reform::Y1752,
+        &Highlights::default(),
+    );
+    assert_eq!(
+        lines.join("\n") + "\n",
+        fs::read_to_string("tests/expected/9-1752.txt")?
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_moon_3_1_2021() -> Result<()> {
+    run(
+        &["--moon", "-3", "-m", "1", "2021"],
+        "tests/expected/moon-3-1-2021.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn moon_with_events() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--moon", "--events", "tests/inputs/events.txt"])
+        .args(["--list-events", "-m", "12", "2020"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(
+            "\n2020-12-14  New moon\n\
+            2020-12-24  Christmas Eve\n\
+            2020-12-25  Christmas Day\n\
+            2020-12-30  Full moon\n",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_year_month_arg() -> Result<()> {
+    run(&["2020-04"], "tests/expected/4-2020.txt")?;
+    run(&["4", "2020"], "tests/expected/4-2020.txt")?;
+    run(&["apr", "2020"], "tests/expected/4-2020.txt")?;
+    run(&["-3", "2020-01"], "tests/expected/3-1-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_month_arg_and_m() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "3", "2020-04"])
+        .assert()
+        .failure()
+        .stderr("-m cannot be used with a month argument\n");
+    Command::cargo_bin(PRG)?
+        .args(["-m", "3", "4", "2020"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '-m <MONTH>' cannot be used with '[YEAR]'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_year_month_arg() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("2020-13")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '2020-13' for '[YEAR]': invalid month in \"2020-13\"",
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["13", "2020"])
+        .assert()
+        .failure()
+        .stderr("month \"13\" not in the range 1 through 12\n");
+    Command::cargo_bin(PRG)?
+        .args(["2020-04", "2020"])
+        .assert()
+        .failure()
+        .stderr("a year cannot follow YEAR-MONTH\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn footer() -> Result<()> {
+    let today = chrono::Local::now().date_naive();
+    Command::cargo_bin(PRG)?
+        .args(["--footer", "-m", "4", "2020"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n{}\n",
+            fs::read_to_string("tests/expected/4-2020.txt")?,
+            calr::format_footer(today)
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_compact_2_2015() -> Result<()> {
+    run(
+        &["--compact", "-m", "2", "2015"],
+        "tests/expected/compact-2-2015.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_compact_w_3_2_2015() -> Result<()> {
+    run(
+        &["--compact", "-w", "-3", "2015-02"],
+        "tests/expected/compact-w-3-2-2015.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn compact_year() -> Result<()> {
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["--compact", "2015"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.split('\n').collect();
+    assert_eq!(lines.len(), 35);
+    Ok(())
+}
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+
+# Compare cutr with GNU cut on a wide CSV: 20000 lines of 500 fields
+WIDE=$(mktemp)
+trap 'rm -f "$WIDE"' EXIT
+
+awk 'BEGIN {
+    for (i = 0; i < 20000; i++) {
+        line = "f" i "_0"
+        for (j = 1; j < 500; j++) line = line ",f" i "_" j
+        print line
+    }
+}' > "$WIDE"
+
+cargo build --release --quiet || exit 1
+CUTR=target/release/cutr
+
+for ARGS in "-d , -f 1-500" "-d , -f 1,250,500" "-c 1-3000" "-b 1-3000"; do
+    echo "== $ARGS"
+    echo -n "cut:  "
+    { time cut $ARGS "$WIDE" > /dev/null; } 2>&1 | grep real
+    echo -n "cutr:"
+    { time $CUTR $ARGS "$WIDE" > /dev/null; } 2>&1 | grep real
+done
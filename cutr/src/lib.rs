@@ -0,0 +1,1051 @@
+use crate::Extract::*;
+use anyhow::{Error, Result};
+use clap::{builder::TypedValueParser, error::ErrorKind, Parser, ValueEnum};
+use csv::{ByteRecord, Terminator};
+use regex::RegexBuilder;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    num::NonZeroUsize,
+    ops::{Range, RangeFrom, RangeTo},
+    os::unix::ffi::OsStrExt,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone)]
+struct ByteParser {}
+
+impl ByteParser {
+    fn new() -> ByteParser {
+        ByteParser {}
+    }
+}
+
+impl TypedValueParser for ByteParser {
+    type Value = u8;
+
+    fn parse_ref(
+        &self,
+        _: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let bytes = value.as_bytes().to_owned();
+        if bytes.len() != 1 {
+            let err = clap::Error::raw(
+                ErrorKind::ValueValidation,
+                format!(
+                    "--{} \"{}\" must be a single byte\n",
+                    arg.unwrap().get_long().unwrap(),
+                    value.to_string_lossy()
+                ),
+            );
+            return Err(err);
+        }
+        Ok(bytes.first().unwrap().to_owned())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum AnyRange<T> {
+    From(RangeFrom<T>),
+    To(RangeTo<T>),
+    Range(Range<T>),
+}
+
+impl AnyRange<usize> {
+    /// The positions of this range that fall within `0..len`.
+    fn bounded(&self, len: usize) -> Range<usize> {
+        let (start, end) = match self {
+            AnyRange::From(from) => (from.start, len),
+            AnyRange::To(to) => (0, to.end),
+            AnyRange::Range(range) => (range.start, range.end),
+        };
+        let end = end.min(len);
+        start.min(end)..end
+    }
+}
+
+type PositionList = Vec<AnyRange<usize>>;
+
+#[derive(Clone)]
+struct PositionListParser {}
+
+impl PositionListParser {
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl TypedValueParser for PositionListParser {
+    type Value = PositionList;
+
+    fn parse_ref(
+        &self,
+        _: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value = value.to_string_lossy();
+        parse_pos(&value).map_err(|message| {
+            let message = format!("{} for {}", message, arg.map(|a| a.to_string()).unwrap());
+            clap::Error::raw(ErrorKind::ValueValidation, format!("{message}\n"))
+        })
+    }
+}
+
+/// How `--pad` sizes the columns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pad {
+    /// As wide as the widest value in the column
+    Auto,
+    /// These widths, one per column; later columns are not padded
+    Widths(Vec<usize>),
+}
+
+fn parse_pad(value: &str) -> Result<Pad> {
+    if value == "auto" {
+        return Ok(Pad::Auto);
+    }
+    value
+        .split(',')
+        .map(|width| {
+            width
+                .parse()
+                .map_err(|_| Error::msg(format!("illegal width: \"{width}\"")))
+        })
+        .collect::<Result<_>>()
+        .map(Pad::Widths)
+}
+
+fn parse_index(value: &str) -> Result<usize> {
+    let value_error = || Error::msg(format!("illegal list value: \"{value}\""));
+    if value.starts_with('+') {
+        Err(value_error())
+    } else {
+        value
+            .parse::<NonZeroUsize>()
+            .map(|val| val.get())
+            .map_err(|_| value_error())
+    }
+}
+
+fn parse_pos(value: &str) -> Result<PositionList> {
+    let from_re = RegexBuilder::new(r"^(\d+)-$").build().unwrap();
+    let to_re = RegexBuilder::new(r"^-(\d+)$").build().unwrap();
+    let range_re = RegexBuilder::new(r"^(\d+)-(\d+)$").build().unwrap();
+    value
+        .split(',')
+        .map(|val| {
+            parse_index(val)
+                .map(|n| AnyRange::Range(n - 1..n))
+                .or_else(|err| {
+                    from_re.captures(val).ok_or(err).and_then(|cap| {
+                        let start = parse_index(&cap[1])?;
+                        Ok(AnyRange::From(start - 1..))
+                    })
+                })
+                .or_else(|err| {
+                    to_re.captures(val).ok_or(err).and_then(|cap| {
+                        let end = parse_index(&cap[1])?;
+                        Ok(AnyRange::To(..end))
+                    })
+                })
+                .or_else(|err| {
+                    range_re.captures(val).ok_or(err).and_then(|cap| {
+                        let start = parse_index(&cap[1])?;
+                        let end = parse_index(&cap[2])?;
+                        if start < end {
+                            Ok(AnyRange::Range(start - 1..end))
+                        } else {
+                            Err(Error::msg(
+                                format!("First number in range ({start}) must be lower than second number ({end})"),
+                            ))
+                        }
+                    })
+                })
+        })
+        .collect::<Result<_, _>>()
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Rust cut", version)]
+pub struct Config {
+    #[arg(value_name = "FILE", default_value = "-", help = "Input file(s)")]
+    files: Vec<String>,
+
+    #[arg(
+        short = 'd',
+        long = "delim",
+        value_name = "DELIMITER",
+        default_value = "\t",
+        help = "Field delimiter",
+        value_parser(ByteParser::new())
+    )]
+    delimiter: u8,
+
+    #[arg(
+        short = 'f',
+        long = "fields",
+        value_name = "FIELDS",
+        help = "Selected fields",
+        value_parser(PositionListParser::new()),
+        allow_hyphen_values(true),
+        required(true),
+        conflicts_with_all(["bytes", "chars", "graphemes"]),
+    )]
+    fields: Option<PositionList>,
+
+    #[arg(
+        short = 'b',
+        long = "bytes",
+        value_name = "BYTES",
+        help = "Selected bytes",
+        value_parser(PositionListParser::new()),
+        allow_hyphen_values(true),
+        required(true),
+        conflicts_with_all(["fields", "chars", "graphemes"]),
+    )]
+    bytes: Option<PositionList>,
+
+    #[arg(
+        short = 'c',
+        long = "chars",
+        value_name = "CHARS",
+        help = "Selected characters",
+        value_parser(PositionListParser::new()),
+        allow_hyphen_values(true),
+        required(true),
+        conflicts_with_all(["fields", "bytes", "graphemes"]),
+    )]
+    chars: Option<PositionList>,
+
+    #[arg(
+        short = 'g',
+        long = "graphemes",
+        value_name = "GRAPHEMES",
+        help = "Selected extended grapheme clusters",
+        value_parser(PositionListParser::new()),
+        allow_hyphen_values(true),
+        required(true),
+        conflicts_with_all(["fields", "bytes", "chars"]),
+    )]
+    graphemes: Option<PositionList>,
+
+    #[arg(
+        short = 'n',
+        long = "no-split-multibyte",
+        value_name = "MODE",
+        help = "With --bytes, never split a multibyte character",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "drop",
+        conflicts_with_all(["fields", "chars", "graphemes"]),
+    )]
+    no_split: Option<NoSplit>,
+
+    #[arg(
+        short = 's',
+        long = "only-delimited",
+        help = "Do not print lines without a field delimiter",
+        conflicts_with_all(["bytes", "chars", "graphemes"]),
+    )]
+    only_delimited: bool,
+
+    #[arg(
+        long = "csv",
+        help = "Parse comma-separated records, honoring quotes",
+        conflicts_with_all(["bytes", "chars", "graphemes", "delimiter", "tsv"]),
+    )]
+    csv: bool,
+
+    #[arg(
+        long = "tsv",
+        help = "Parse tab-separated records, honoring quotes",
+        conflicts_with_all(["bytes", "chars", "graphemes", "delimiter"]),
+    )]
+    tsv: bool,
+
+    #[arg(
+        short = 'z',
+        long = "zero-terminated",
+        help = "Line delimiter is NUL, not newline"
+    )]
+    zero_terminated: bool,
+
+    #[arg(
+        long = "complement",
+        help = "Select the bytes, characters, graphemes or fields not in the list"
+    )]
+    complement: bool,
+
+    #[arg(
+        long = "pad",
+        value_name = "WIDTHS",
+        help = "Align the selected fields in columns, WIDTHS wide or as wide as their widest value",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "auto",
+        value_parser = parse_pad,
+        conflicts_with_all(["bytes", "chars", "graphemes", "csv", "tsv"]),
+    )]
+    pad: Option<Pad>,
+
+    #[arg(
+        long = "gnu-order",
+        help = "Select in ascending order and at most once, like GNU cut"
+    )]
+    gnu_order: bool,
+}
+
+impl Config {
+    fn get_extract(&self) -> Option<Extract> {
+        let positions = |opt: &PositionList| {
+            if self.gnu_order {
+                normalize(opt)
+            } else {
+                opt.to_owned()
+            }
+        };
+        self.fields
+            .as_ref()
+            .map(|opt| Fields(positions(opt)))
+            .or(self.bytes.as_ref().map(|opt| Bytes(positions(opt))))
+            .or(self.chars.as_ref().map(|opt| Chars(positions(opt))))
+            .or(self.graphemes.as_ref().map(|opt| Graphemes(positions(opt))))
+    }
+}
+
+/// How `--no-split-multibyte` treats a character cut by a byte range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum NoSplit {
+    /// Leave out the bytes of the cut character
+    Drop,
+    /// Widen the range to include the whole character
+    Extend,
+}
+
+#[derive(Clone, Debug)]
+enum Extract {
+    Fields(PositionList),
+    Bytes(PositionList),
+    Chars(PositionList),
+    Graphemes(PositionList),
+}
+
+fn open(filename: &str) -> Result<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+/// The positions in `pos` as sorted ranges, with overlapping and adjacent
+/// ones merged, so each position is selected once and in ascending order.
+fn normalize(pos: &[AnyRange<usize>]) -> PositionList {
+    // each range as its start and its end, which is None when unbounded
+    let mut bounds = pos
+        .iter()
+        .map(|range| match range {
+            AnyRange::From(from) => (from.start, None),
+            AnyRange::To(to) => (0, Some(to.end)),
+            AnyRange::Range(range) => (range.start, Some(range.end)),
+        })
+        .collect::<Vec<_>>();
+    bounds.sort();
+    let mut merged: Vec<(usize, Option<usize>)> = vec![];
+    for (start, end) in bounds {
+        match merged.last_mut() {
+            Some((_, last_end)) if last_end.is_none_or(|last_end| start <= last_end) => {
+                *last_end = last_end.zip(end).map(|(a, b)| a.max(b));
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(start, end)| match end {
+            Some(end) => AnyRange::Range(start..end),
+            None => AnyRange::From(start..),
+        })
+        .collect()
+}
+
+/// The positions in `0..len` that are not in `pos`, as ascending ranges.
+fn complement(pos: &[AnyRange<usize>], len: usize) -> PositionList {
+    let mut selected = vec![false; len];
+    for range in pos {
+        selected[range.bounded(len)].fill(true);
+    }
+    let mut ranges = vec![];
+    let mut start = None;
+    for (index, &selected) in selected.iter().enumerate() {
+        match (selected, start) {
+            (false, None) => start = Some(index),
+            (true, Some(from)) => {
+                ranges.push(AnyRange::Range(from..index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        ranges.push(AnyRange::Range(from..len));
+    }
+    ranges
+}
+
+// Each line is split into its units once, into a buffer the caller keeps
+// from line to line, and the selected ranges are sliced out of that, so wide
+// selections do not rescan the line for every position.
+fn extract_chars(line: &str, char_pos: &[AnyRange<usize>], chars: &mut Vec<char>) -> String {
+    chars.clear();
+    chars.extend(line.chars());
+    char_pos
+        .iter()
+        .flat_map(|range| &chars[range.bounded(chars.len())])
+        .collect()
+}
+
+fn extract_graphemes(
+    line: &str,
+    grapheme_pos: &[AnyRange<usize>],
+    spans: &mut Vec<Range<usize>>,
+) -> String {
+    spans.clear();
+    spans.extend(
+        line.grapheme_indices(true)
+            .map(|(start, grapheme)| start..start + grapheme.len()),
+    );
+    grapheme_pos
+        .iter()
+        .flat_map(|range| &spans[range.bounded(spans.len())])
+        .map(|span| &line[span.clone()])
+        .collect()
+}
+
+fn extract_bytes(line: &str, char_pos: &[AnyRange<usize>]) -> String {
+    let bytes = line.as_bytes();
+    let extracted_bytes = char_pos
+        .iter()
+        .flat_map(|range| &bytes[range.bounded(bytes.len())])
+        .copied()
+        .collect::<Vec<u8>>();
+    String::from_utf8_lossy(&extracted_bytes).to_string()
+}
+
+/// Like `extract_bytes`, but each range is first moved to character
+/// boundaries, widening or narrowing it as `no_split` says.
+fn extract_whole_chars(line: &str, byte_pos: &[AnyRange<usize>], no_split: NoSplit) -> String {
+    let mut extracted = String::new();
+    for range in byte_pos {
+        let Range { mut start, mut end } = range.bounded(line.len());
+        match no_split {
+            NoSplit::Drop => {
+                while !line.is_char_boundary(start) {
+                    start += 1;
+                }
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+            }
+            NoSplit::Extend => {
+                while !line.is_char_boundary(start) {
+                    start -= 1;
+                }
+                while !line.is_char_boundary(end) {
+                    end += 1;
+                }
+            }
+        }
+        if start < end {
+            extracted.push_str(&line[start..end]);
+        }
+    }
+    extracted
+}
+
+/// The fields of `line` selected by `char_pos`. `spans` is left holding where
+/// each field of the line starts and ends.
+fn select_fields<'a>(
+    line: &'a str,
+    delim: u8,
+    char_pos: &[AnyRange<usize>],
+    spans: &mut Vec<Range<usize>>,
+) -> Vec<&'a str> {
+    let delim = delim as char;
+    spans.clear();
+    let mut start = 0;
+    for field in line.split(delim) {
+        spans.push(start..start + field.len());
+        start += field.len() + delim.len_utf8();
+    }
+    char_pos
+        .iter()
+        .flat_map(|range| &spans[range.bounded(spans.len())])
+        .map(|span| &line[span.clone()])
+        .collect()
+}
+
+fn extract_fields(
+    line: &str,
+    delim: u8,
+    char_pos: &[AnyRange<usize>],
+    spans: &mut Vec<Range<usize>>,
+) -> String {
+    select_fields(line, delim, char_pos, spans).join(&String::from(delim as char))
+}
+
+/// Write `fields` separated by two spaces, each but the last padded to its
+/// width in `widths`.
+fn write_padded(
+    writer: &mut impl Write,
+    fields: &[&str],
+    widths: &[usize],
+    terminator: u8,
+) -> Result<()> {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b"  ")?;
+        }
+        match widths.get(index) {
+            Some(&width) if index + 1 < fields.len() => write!(writer, "{field:<width$}")?,
+            _ => write!(writer, "{field}")?,
+        }
+    }
+    writer.write_all(&[terminator])?;
+    Ok(())
+}
+
+fn extract_record<'a>(record: &'a ByteRecord, field_pos: &[AnyRange<usize>]) -> Vec<&'a [u8]> {
+    field_pos
+        .iter()
+        .flat_map(|range| range.bounded(record.len()).map(|index| &record[index]))
+        .collect()
+}
+
+/// Cut the fields of CSV or TSV records, quoting output fields as needed.
+fn cut_csv(
+    reader: impl BufRead,
+    config: &Config,
+    field_pos: &PositionList,
+    writer: impl Write,
+) -> Result<()> {
+    let delimiter = if config.tsv { b'\t' } else { b',' };
+    let terminator = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .terminator(if config.zero_terminated {
+            Terminator::Any(terminator)
+        } else {
+            Terminator::CRLF
+        })
+        .from_reader(reader);
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .delimiter(delimiter)
+        .terminator(Terminator::Any(terminator))
+        .from_writer(writer);
+    for record in reader.byte_records() {
+        let record = record?;
+        if config.only_delimited && record.len() < 2 {
+            continue;
+        }
+        let pos = if config.complement {
+            complement(field_pos, record.len())
+        } else {
+            field_pos.clone()
+        };
+        writer.write_record(extract_record(&record, &pos))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn get_args() -> Result<Config> {
+    Ok(Config::parse())
+}
+
+/// The lines of `reader`, or its NUL-terminated records with `zero_terminated`,
+/// which must be UTF-8.
+fn lines(reader: impl BufRead, zero_terminated: bool) -> impl Iterator<Item = Result<String>> {
+    let terminator = if zero_terminated { b'\0' } else { b'\n' };
+    reader.split(terminator).map(move |line| {
+        let mut line = line?;
+        if !zero_terminated && line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        String::from_utf8(line).map_err(|_| {
+            Error::from(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ))
+        })
+    })
+}
+
+/// Cut the fields of every line and align them in columns. Automatic widths
+/// need every line of the input before the first can be written.
+fn cut_padded(
+    reader: impl BufRead,
+    config: &Config,
+    field_pos: &PositionList,
+    pad: &Pad,
+    mut writer: impl Write,
+) -> Result<()> {
+    let terminator = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut rows = vec![];
+    let mut spans = vec![];
+    for line in lines(reader, config.zero_terminated) {
+        let line = line?;
+        if config.only_delimited && !line.as_bytes().contains(&config.delimiter) {
+            continue;
+        }
+        let pos = if config.complement {
+            complement(field_pos, line.split(config.delimiter as char).count())
+        } else {
+            field_pos.clone()
+        };
+        let fields = select_fields(&line, config.delimiter, &pos, &mut spans);
+        match pad {
+            Pad::Widths(widths) => write_padded(&mut writer, &fields, widths, terminator)?,
+            Pad::Auto => rows.push(fields.into_iter().map(String::from).collect::<Vec<_>>()),
+        }
+    }
+    let mut widths = vec![];
+    for row in &rows {
+        for (index, field) in row.iter().enumerate() {
+            let width = field.chars().count();
+            match widths.get_mut(index) {
+                Some(max) => *max = width.max(*max),
+                None => widths.push(width),
+            }
+        }
+    }
+    for row in &rows {
+        let fields = row.iter().map(String::as_str).collect::<Vec<_>>();
+        write_padded(&mut writer, &fields, &widths, terminator)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Cut every line of `reader` as selected by `config`, writing the result to
+/// `writer`.
+pub fn cut(config: &Config, reader: impl BufRead, mut writer: impl Write) -> Result<()> {
+    let Some(extract) = config.get_extract() else {
+        return Ok(());
+    };
+    if config.csv || config.tsv {
+        let Fields(pos) = extract else {
+            return Ok(());
+        };
+        return cut_csv(reader, config, &pos, writer);
+    }
+    if let (Some(pad), Fields(pos)) = (&config.pad, &extract) {
+        return cut_padded(reader, config, pos, pad, writer);
+    }
+    let terminator = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut chars = vec![];
+    let mut spans = vec![];
+    for line in lines(reader, config.zero_terminated) {
+        let line = line?;
+        if config.only_delimited && !line.as_bytes().contains(&config.delimiter) {
+            continue;
+        }
+        let extracted = match &extract {
+            Bytes(pos) => {
+                let pos = if config.complement {
+                    complement(pos, line.len())
+                } else {
+                    pos.clone()
+                };
+                match config.no_split {
+                    Some(no_split) => extract_whole_chars(&line, &pos, no_split),
+                    None => extract_bytes(&line, &pos),
+                }
+            }
+            Chars(pos) => {
+                let pos = if config.complement {
+                    complement(pos, line.chars().count())
+                } else {
+                    pos.clone()
+                };
+                extract_chars(&line, &pos, &mut chars)
+            }
+            Graphemes(pos) => {
+                let pos = if config.complement {
+                    complement(pos, line.graphemes(true).count())
+                } else {
+                    pos.clone()
+                };
+                extract_graphemes(&line, &pos, &mut spans)
+            }
+            Fields(pos) => {
+                let pos = if config.complement {
+                    complement(pos, line.split(config.delimiter as char).count())
+                } else {
+                    pos.clone()
+                };
+                extract_fields(&line, config.delimiter, &pos, &mut spans)
+            }
+        };
+        writer.write_all(extracted.as_bytes())?;
+        writer.write_all(&[terminator])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Cut every file in `config` to stdout. Files that cannot be read are
+/// reported on stderr and skipped.
+pub fn run(config: Config) -> Result<()> {
+    for filename in &config.files {
+        if let Err(err) = open(filename).and_then(|reader| cut(&config, reader, io::stdout())) {
+            eprintln!("{filename}: {err}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_cut() {
+        let config = Config::try_parse_from(["cutr", "-s", "-f", "2"]).unwrap();
+        let mut out = vec![];
+        cut(&config, &b"a\tb\r\nc\nd\te\tf"[..], &mut out).unwrap();
+        assert_eq!(out, b"b\ne\n");
+
+        let config = Config::try_parse_from(["cutr", "-z", "-c", "2-"]).unwrap();
+        let mut out = vec![];
+        cut(&config, &b"abc\0\xff\0"[..], &mut out).unwrap_err();
+        assert_eq!(out, b"bc\0");
+
+        let config = Config::try_parse_from(["cutr", "--csv", "-f", "2"]).unwrap();
+        let mut out = vec![];
+        cut(&config, &b"a,\"b,c\"\n"[..], &mut out).unwrap();
+        assert_eq!(out, b"\"b,c\"\n");
+    }
+
+    #[test]
+    fn test_parser_pos() {
+        let res = parse_pos("");
+        assert!(res.is_err());
+
+        let res = parse_pos("0");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"0\"");
+
+        let res = parse_pos("0-1");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"0\"");
+
+        let res = parse_pos("+1");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"+1\"");
+
+        let res = parse_pos("+1-2");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"+1-2\"");
+
+        let res = parse_pos("1-+2");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"1-+2\"");
+
+        let res = parse_pos("1,a");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"a\"");
+
+        let res = parse_pos("1-a");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"1-a\"");
+
+        let res = parse_pos("a-1");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"a-1\"");
+
+        let res = parse_pos("-");
+        assert!(res.is_err());
+
+        let res = parse_pos(",");
+        assert!(res.is_err());
+
+        let res = parse_pos("1,");
+        assert!(res.is_err());
+
+        let res = parse_pos("1-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::From(0..)]);
+
+        let res = parse_pos("1-1-1");
+        assert!(res.is_err());
+
+        let res = parse_pos("1-1-a");
+        assert!(res.is_err());
+
+        let res = parse_pos("1-1");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "First number in range (1) must be lower than second number (1)"
+        );
+
+        let res = parse_pos("2-1");
+        assert!(res.is_err());
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "First number in range (2) must be lower than second number (1)"
+        );
+
+        // normal cases
+
+        let res = parse_pos("1");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::Range(0..1)]);
+
+        let res = parse_pos("01");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::Range(0..1)]);
+
+        let res = parse_pos("1,3");
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![AnyRange::Range(0..1), AnyRange::Range(2..3)]
+        );
+
+        let res = parse_pos("001,0003");
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![AnyRange::Range(0..1), AnyRange::Range(2..3)]
+        );
+
+        let res = parse_pos("1-3");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::Range(0..3)]);
+
+        let res = parse_pos("1,7,3-5");
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![
+                AnyRange::Range(0..1),
+                AnyRange::Range(6..7),
+                AnyRange::Range(2..5)
+            ]
+        );
+
+        let res = parse_pos("15,19-20");
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![AnyRange::Range(14..15), AnyRange::Range(18..20)]
+        );
+
+        let res = parse_pos("-3");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::To(..3)]);
+
+        let res = parse_pos("1,-3");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::Range(0..1), AnyRange::To(..3)]);
+
+        let res = parse_pos("-3,5-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::To(..3), AnyRange::From(4..)]);
+
+        let res = parse_pos("3-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![AnyRange::From(2..)]);
+
+        let res = parse_pos("1-3,5-");
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![AnyRange::Range(0..3), AnyRange::From(4..)]
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        assert_eq!(complement(&[AnyRange::Range(0..1)], 0), vec![]);
+        assert_eq!(
+            complement(&[AnyRange::Range(1..2)], 4),
+            vec![AnyRange::Range(0..1), AnyRange::Range(2..4)]
+        );
+        assert_eq!(
+            complement(&[AnyRange::From(2..), AnyRange::Range(0..1)], 5),
+            vec![AnyRange::Range(1..2)]
+        );
+        assert_eq!(
+            complement(&[AnyRange::To(..2), AnyRange::Range(6..9)], 4),
+            vec![AnyRange::Range(2..4)]
+        );
+        assert_eq!(complement(&[AnyRange::From(0..)], 3), vec![]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(&[]), vec![]);
+        assert_eq!(
+            normalize(&[AnyRange::Range(0..1), AnyRange::Range(0..1)]),
+            vec![AnyRange::Range(0..1)]
+        );
+        assert_eq!(
+            normalize(&[
+                AnyRange::Range(6..7),
+                AnyRange::Range(0..1),
+                AnyRange::Range(1..3)
+            ]),
+            vec![AnyRange::Range(0..3), AnyRange::Range(6..7)]
+        );
+        assert_eq!(
+            normalize(&[
+                AnyRange::From(4..),
+                AnyRange::To(..2),
+                AnyRange::Range(5..9)
+            ]),
+            vec![AnyRange::Range(0..2), AnyRange::From(4..)]
+        );
+        assert_eq!(
+            normalize(&[AnyRange::Range(2..5), AnyRange::From(3..)]),
+            vec![AnyRange::From(2..)]
+        );
+    }
+
+    #[test]
+    fn test_parse_pad() {
+        assert_eq!(parse_pad("auto").unwrap(), Pad::Auto);
+        assert_eq!(parse_pad("10").unwrap(), Pad::Widths(vec![10]));
+        assert_eq!(parse_pad("3,0,12").unwrap(), Pad::Widths(vec![3, 0, 12]));
+
+        let res = parse_pad("3,x");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal width: \"x\"");
+        assert!(parse_pad("").is_err());
+        assert!(parse_pad("-1").is_err());
+    }
+
+    #[test]
+    fn test_extract_chars() {
+        let mut chars = vec![];
+        assert_eq!(
+            extract_chars("", &[AnyRange::Range(0..1)], &mut chars),
+            "".to_string()
+        );
+        assert_eq!(
+            extract_chars("ábc", &[AnyRange::Range(0..1)], &mut chars),
+            "á".to_string()
+        );
+        assert_eq!(
+            extract_chars(
+                "ábc",
+                &[AnyRange::Range(0..1), AnyRange::Range(2..3)],
+                &mut chars
+            ),
+            "ác".to_string()
+        );
+        assert_eq!(
+            extract_chars("ábc", &[AnyRange::Range(0..3)], &mut chars),
+            "ábc".to_string()
+        );
+        assert_eq!(
+            extract_chars(
+                "ábc",
+                &[AnyRange::Range(2..3), AnyRange::Range(1..2)],
+                &mut chars
+            ),
+            "cb".to_string()
+        );
+        assert_eq!(
+            extract_chars(
+                "ábc",
+                &[
+                    AnyRange::Range(0..1),
+                    AnyRange::Range(1..2),
+                    AnyRange::Range(4..5)
+                ],
+                &mut chars
+            ),
+            "áb".to_string()
+        );
+    }
+
+    #[test]
+    fn test_extract_graphemes() {
+        let mut spans = vec![];
+        assert_eq!(
+            extract_graphemes("", &[AnyRange::Range(0..1)], &mut spans),
+            ""
+        );
+        assert_eq!(
+            extract_graphemes("e\u{301}tude", &[AnyRange::Range(0..1)], &mut spans),
+            "e\u{301}"
+        );
+        assert_eq!(
+            extract_graphemes(
+                "👍🏽ok",
+                &[AnyRange::To(..2), AnyRange::Range(5..6)],
+                &mut spans
+            ),
+            "👍🏽o"
+        );
+        assert_eq!(
+            extract_graphemes("🇫🇷🇩🇪", &[AnyRange::From(1..)], &mut spans),
+            "🇩🇪"
+        );
+    }
+
+    #[test]
+    fn test_extract_whole_chars() {
+        let pos = [AnyRange::Range(0..1)];
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Drop), "");
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Extend), "á");
+
+        let pos = [AnyRange::Range(1..3)];
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Drop), "b");
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Extend), "áb");
+
+        let pos = [AnyRange::From(1..), AnyRange::To(..2)];
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Drop), "bcá");
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Extend), "ábcá");
+
+        let pos = [AnyRange::Range(5..9)];
+        assert_eq!(extract_whole_chars("ábc", &pos, NoSplit::Drop), "");
+    }
+
+    #[test]
+    fn test_extract_bytes() {
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(0..1)]),
+            "�".to_string()
+        );
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(0..2)]),
+            "á".to_string()
+        );
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(0..3)]),
+            "áb".to_string()
+        );
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(0..4)]),
+            "ábc".to_string()
+        );
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(3..4), AnyRange::Range(2..3)]),
+            "cb".to_string()
+        );
+        assert_eq!(
+            extract_bytes("ábc", &[AnyRange::Range(0..2), AnyRange::Range(5..6)]),
+            "á".to_string()
+        );
+    }
+}
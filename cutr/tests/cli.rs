@@ -9,6 +9,7 @@ const PRG: &str = "cutr";
 const CSV: &str = "tests/inputs/movies1.csv";
 const TSV: &str = "tests/inputs/movies1.tsv";
 const BOOKS: &str = "tests/inputs/books.tsv";
+const PARTIAL: &str = "tests/inputs/partial.tsv";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -340,3 +341,281 @@ fn tsv_c1_8() -> Result<()> {
 fn repeated_value() -> Result<()> {
     run(&[BOOKS, "-c", "1,1"], "tests/expected/books.c1,1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn tsv_f2_complement() -> Result<()> {
+    run(
+        &[TSV, "-f", "2", "--complement"],
+        "tests/expected/movies1.tsv.f2.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_f1_3_complement() -> Result<()> {
+    run(
+        &[CSV, "-f", "1,3", "-d", ",", "--complement"],
+        "tests/expected/movies1.csv.f1,3.dcomma.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b_3_complement() -> Result<()> {
+    run_lossy(
+        &[TSV, "-b", "-3", "--complement"],
+        "tests/expected/movies1.tsv.b-3.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_c2_4_complement() -> Result<()> {
+    run(
+        &[TSV, "-c", "2,4-", "--complement"],
+        "tests/expected/movies1.tsv.c2,4-.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn partial_f1() -> Result<()> {
+    run(&[PARTIAL, "-f", "1"], "tests/expected/partial.tsv.f1.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn partial_f2_only_delimited() -> Result<()> {
+    run(
+        &[PARTIAL, "-s", "-f", "2"],
+        "tests/expected/partial.tsv.f2.s.out",
+    )?;
+    run(
+        &[PARTIAL, "--only-delimited", "-f", "2"],
+        "tests/expected/partial.tsv.f2.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_only_delimited_without_fields() -> Result<()> {
+    dies(
+        &[PARTIAL, "-s", "-c", "1"],
+        "'--only-delimited' cannot be used with '--chars <CHARS>'",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn records_f2_zero_terminated() -> Result<()> {
+    run(
+        &["tests/inputs/records.tsv", "-z", "-f", "2"],
+        "tests/expected/records.tsv.f2.z.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn records_c1_2_zero_terminated() -> Result<()> {
+    run(
+        &["tests/inputs/records.tsv", "--zero-terminated", "-c", "1-2"],
+        "tests/expected/records.tsv.c1-2.z.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn quoted_csv_f1_3() -> Result<()> {
+    run(
+        &["tests/inputs/quoted.csv", "--csv", "-f", "1,3"],
+        "tests/expected/quoted.csv.f1,3.csv.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn quoted_csv_f2_only_delimited_complement() -> Result<()> {
+    run(
+        &[
+            "tests/inputs/quoted.csv",
+            "--csv",
+            "-s",
+            "--complement",
+            "-f",
+            "2",
+        ],
+        "tests/expected/quoted.csv.f2.csv.s.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn quoted_tsv_f1() -> Result<()> {
+    run(
+        &["tests/inputs/quoted.tsv", "--tsv", "-f", "1"],
+        "tests/expected/quoted.tsv.f1.tsv.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_csv_with_delimiter() -> Result<()> {
+    dies(
+        &["tests/inputs/quoted.csv", "--csv", "-d", ";", "-f", "1"],
+        "'--csv' cannot be used with '--delim <DELIMITER>'",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_by_default() -> Result<()> {
+    let input = fs::read_to_string(TSV)?;
+    let expected = fs::read_to_string("tests/expected/movies1.tsv.f1.out")?;
+    let output = Command::cargo_bin(PRG)?
+        .args(["-f", "1"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_dash_between_files() -> Result<()> {
+    let input = fs::read_to_string(TSV)?;
+    let expected = fs::read_to_string("tests/expected/movies1.tsv.f1.out")?.repeat(3);
+    let output = Command::cargo_bin(PRG)?
+        .args(["-f", "1", TSV, "-", TSV])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn graphemes_g1_6() -> Result<()> {
+    run(
+        &["tests/inputs/graphemes.tsv", "-g", "1,6-"],
+        "tests/expected/graphemes.tsv.g1,6-.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn graphemes_g1_6_complement() -> Result<()> {
+    run(
+        &[
+            "tests/inputs/graphemes.tsv",
+            "--graphemes",
+            "1,6-",
+            "--complement",
+        ],
+        "tests/expected/graphemes.tsv.g1,6-.complement.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_digit_graphemes() -> Result<()> {
+    let bad = random_string();
+    dies(
+        &[CSV, "-g", &bad],
+        &format!(r#"illegal list value: "{}""#, &bad),
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b1_8_no_split() -> Result<()> {
+    run(
+        &[TSV, "-b", "1-8", "-n"],
+        "tests/expected/movies1.tsv.b1-8.n.out",
+    )?;
+    run(
+        &[TSV, "-b", "1-8", "--no-split-multibyte=drop"],
+        "tests/expected/movies1.tsv.b1-8.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b1_8_no_split_extend() -> Result<()> {
+    run(
+        &[TSV, "-b", "1-8", "--no-split-multibyte=extend"],
+        "tests/expected/movies1.tsv.b1-8.n-extend.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_split_with_chars() -> Result<()> {
+    dies(
+        &[TSV, "-c", "1-8", "-n"],
+        "'--chars <CHARS>' cannot be used with '--no-split-multibyte[=<MODE>]'",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f3_1_2_2_gnu_order() -> Result<()> {
+    run(
+        &[TSV, "-f", "3,1-2,2", "--gnu-order"],
+        "tests/expected/movies1.tsv.f3,1-2,2.gnu.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_c5_1_3_2_gnu_order() -> Result<()> {
+    run(
+        &[TSV, "-c", "5-,1-3,2", "--gnu-order"],
+        "tests/expected/movies1.tsv.c5-,1-3,2.gnu.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f1_3_2_pad() -> Result<()> {
+    run(
+        &[TSV, "-f", "1,3,2", "--pad"],
+        "tests/expected/movies1.tsv.f1,3,2.pad.out",
+    )?;
+    run(
+        &[TSV, "-f", "1,3,2", "--pad=auto"],
+        "tests/expected/movies1.tsv.f1,3,2.pad.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f1_pad_widths() -> Result<()> {
+    run(
+        &[TSV, "-f", "1-", "--pad=5,12"],
+        "tests/expected/movies1.tsv.f1-.pad5,12.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn partial_f1_2_only_delimited_pad() -> Result<()> {
+    run(
+        &[PARTIAL, "-s", "-f", "1-2", "--pad"],
+        "tests/expected/partial.tsv.f1-2.s.pad.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pad_width() -> Result<()> {
+    dies(&[TSV, "-f", "1", "--pad=5,x"], r#"illegal width: "x""#)
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use std::{
+    ffi::OsStr,
+    fs::{FileType, Metadata},
+    path::Path,
+};
+
+/// A directory entry produced by either traversal backend.
+#[derive(Debug)]
+pub enum Entry {
+    Walk(walkdir::DirEntry),
+    Ignore(ignore::DirEntry),
+}
+
+impl From<walkdir::DirEntry> for Entry {
+    fn from(entry: walkdir::DirEntry) -> Self {
+        Entry::Walk(entry)
+    }
+}
+
+impl From<ignore::DirEntry> for Entry {
+    fn from(entry: ignore::DirEntry) -> Self {
+        Entry::Ignore(entry)
+    }
+}
+
+impl Entry {
+    pub fn path(&self) -> &Path {
+        match self {
+            Entry::Walk(entry) => entry.path(),
+            Entry::Ignore(entry) => entry.path(),
+        }
+    }
+
+    pub fn file_name(&self) -> &OsStr {
+        match self {
+            Entry::Walk(entry) => entry.file_name(),
+            Entry::Ignore(entry) => entry.file_name(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            Entry::Walk(entry) => entry.depth(),
+            Entry::Ignore(entry) => entry.depth(),
+        }
+    }
+
+    pub fn file_type(&self) -> FileType {
+        match self {
+            Entry::Walk(entry) => entry.file_type(),
+            // only stdin entries lack a file type, and findr never walks stdin
+            Entry::Ignore(entry) => entry.file_type().unwrap(),
+        }
+    }
+
+    pub fn metadata(&self) -> Result<Metadata> {
+        Ok(match self {
+            Entry::Walk(entry) => entry.metadata()?,
+            Entry::Ignore(entry) => entry.metadata()?,
+        })
+    }
+}
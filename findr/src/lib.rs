@@ -1,12 +1,20 @@
-use anyhow::Result;
+mod entry;
+mod printf;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
 use clap::{
     builder::{PossibleValue, TypedValueParser},
     error::{ContextKind, ContextValue, ErrorKind},
     Parser, ValueEnum,
 };
+use entry::Entry;
+use glob::Pattern;
+use ignore::WalkBuilder;
 use regex::Regex;
-use std::{fmt::Debug, os::unix::fs::MetadataExt};
-use walkdir::{DirEntry, WalkDir};
+use serde_json::json;
+use std::{ffi::OsStr, fmt::Debug, fs, os::unix::fs::MetadataExt, str::FromStr};
+use walkdir::WalkDir;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 enum EntryType {
@@ -29,6 +37,16 @@ impl ValueEnum for EntryType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// One path per line
+    Plain,
+    /// NUL-terminated paths, for xargs -0
+    Null,
+    /// One JSON object per line
+    Json,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum CmpFlag {
     Plus,
@@ -36,12 +54,60 @@ enum CmpFlag {
     None,
 }
 
+impl CmpFlag {
+    fn matches(&self, value: u64, target: u64) -> bool {
+        match self {
+            CmpFlag::Plus => value > target,
+            CmpFlag::Minus => value < target,
+            CmpFlag::None => value == target,
+        }
+    }
+}
+
+/// Numeric argument in find style: `+N` (more than), `-N` (less than) or `N` (exactly).
+#[derive(Debug, Clone)]
+struct NumCmp {
+    value: u64,
+    cmp_flag: CmpFlag,
+}
+
+impl FromStr for NumCmp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cmp_flag, value) = match s.split_at(s.find(|c| c != '+' && c != '-').unwrap_or(0)) {
+            ("+", value) => (CmpFlag::Plus, value),
+            ("-", value) => (CmpFlag::Minus, value),
+            ("", value) => (CmpFlag::None, value),
+            (flag, _) => return Err(format!("invalid flag '{flag}'")),
+        };
+        let value = value
+            .parse()
+            .map_err(|_| format!("invalid number '{value}'"))?;
+        Ok(Self { value, cmp_flag })
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SizeType {
     size: u64,
+    unit: u64,
     cmp_flag: CmpFlag,
 }
 
+impl SizeType {
+    /// Like find, the file size is rounded up to whole units before comparing,
+    /// unless `exact_bytes` is set.
+    fn matches(&self, size: u64, exact_bytes: bool) -> bool {
+        if exact_bytes {
+            self.cmp_flag.matches(size, self.size)
+        } else {
+            self.cmp_flag
+                .matches(size.div_ceil(self.unit), self.size / self.unit)
+        }
+    }
+}
+
 #[derive(Clone)]
 struct SizeTypeParser {}
 
@@ -114,17 +180,25 @@ impl TypedValueParser for SizeTypeParser {
             let blksize: u64 = match unit {
                 "b" => Ok(512),
                 "c" => Ok(1),
-                "k" => Ok(1024),
-                "M" => Ok(1024 * 1024),
-                "G" => Ok(1024 * 1024 * 1024),
-                "T" => Ok(1024 * 1024 * 1024 * 1024),
+                "k" | "KiB" => Ok(1024),
+                "M" | "MiB" => Ok(1024 * 1024),
+                "G" | "GiB" => Ok(1024 * 1024 * 1024),
+                "T" | "TiB" => Ok(1024 * 1024 * 1024 * 1024),
+                "kB" => Ok(1000),
+                "MB" => Ok(1000 * 1000),
+                "GB" => Ok(1000 * 1000 * 1000),
+                "TB" => Ok(1000 * 1000 * 1000 * 1000),
                 "" => Ok(512),
                 _ => Err(validation_error(Some(format!(
-                    "Unit '{unit}' is invalid. Possible values are any of 'b', 'c', 'k', 'M', 'G', 'T' or ''."
+                    "Unit '{unit}' is invalid. Possible values are any of 'b', 'c', 'k', 'M', 'G', 'T', 'kB', 'MB', 'GB', 'TB', 'KiB', 'MiB', 'GiB', 'TiB' or ''."
                 )))),
             }?;
             size *= blksize;
-            Ok(Self::Value { cmp_flag, size })
+            Ok(Self::Value {
+                cmp_flag,
+                size,
+                unit: blksize,
+            })
         } else {
             Err(validation_error(None))
         }
@@ -163,6 +237,46 @@ mod test {
         test_parser!("-10M", CmpFlag::Minus, 10 * 1024 * 1024);
         test_parser!("+2G", CmpFlag::Plus, 2 * 1024 * 1024 * 1024);
         test_parser!("-2G", CmpFlag::Minus, 2 * 1024 * 1024 * 1024);
+        test_parser!("3kB", CmpFlag::None, 3000);
+        test_parser!("+1MB", CmpFlag::Plus, 1000 * 1000);
+        test_parser!("-2GB", CmpFlag::Minus, 2 * 1000 * 1000 * 1000);
+        test_parser!("3KiB", CmpFlag::None, 3 * 1024);
+        test_parser!("+1MiB", CmpFlag::Plus, 1024 * 1024);
+        test_parser!("-2GiB", CmpFlag::Minus, 2 * 1024 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_size_matches() {
+        let size_type = create_parser("1k");
+        assert!(!size_type.matches(0, false));
+        assert!(size_type.matches(1, false));
+        assert!(size_type.matches(1024, false));
+        assert!(!size_type.matches(1025, false));
+        assert!(!size_type.matches(1, true));
+        assert!(size_type.matches(1024, true));
+
+        let size_type = create_parser("-1k");
+        assert!(size_type.matches(0, false));
+        assert!(!size_type.matches(2, false));
+        assert!(size_type.matches(2, true));
+
+        let size_type = create_parser("+1kB");
+        assert!(!size_type.matches(1000, false));
+        assert!(size_type.matches(1001, false));
+        assert!(size_type.matches(1001, true));
+    }
+
+    #[test]
+    fn test_num_cmp() {
+        let num: NumCmp = "+1".parse().unwrap();
+        assert_eq!((num.cmp_flag, num.value), (CmpFlag::Plus, 1));
+        let num: NumCmp = "-3".parse().unwrap();
+        assert_eq!((num.cmp_flag, num.value), (CmpFlag::Minus, 3));
+        let num: NumCmp = "2".parse().unwrap();
+        assert_eq!((num.cmp_flag, num.value), (CmpFlag::None, 2));
+        assert!("++2".parse::<NumCmp>().is_err());
+        assert!("x".parse::<NumCmp>().is_err());
+        assert!("+".parse::<NumCmp>().is_err());
     }
 }
 
@@ -189,13 +303,79 @@ pub struct Config {
     #[arg(long = "maxdepth")]
     max_depth: Option<usize>,
 
-    /// File size. Format is similar to find, e.g. [+-]?[0-9]+[ckMGT]?
+    /// File size. Format is similar to find, e.g. [+-]?[0-9]+[ckMGT]?.
+    /// Decimal (kB, MB, GB, TB) and IEC (KiB, MiB, GiB, TiB) units are also accepted.
+    /// Sizes are rounded up to whole units before comparing
     #[arg(
         long = "size",
         allow_hyphen_values = true,
         value_parser(SizeTypeParser::new())
     )]
     size_type: Option<SizeType>,
+
+    /// Compare --size against the exact byte count instead of rounding up to units
+    #[arg(long = "size-exact-bytes", requires = "size_type")]
+    size_exact_bytes: bool,
+
+    /// Prune directories whose name matches PATTERN (they are not descended into)
+    #[arg(long = "prune", value_name = "PATTERN")]
+    prunes: Vec<Regex>,
+
+    /// Print FORMAT for each entry instead of its path, e.g. '%p %s\n'.
+    /// Directives: %p path, %f name, %h parent dir, %s size, %d depth,
+    /// %m mode, %y type, %TY %Tm %Td %TH %TM %TS modification time, %% literal '%'
+    #[arg(long = "printf", value_name = "FORMAT")]
+    printf: Option<printf::Format>,
+
+    /// Skip entries ignored by .gitignore/.ignore files, and hidden entries
+    #[arg(long = "respect-gitignore")]
+    respect_gitignore: bool,
+
+    /// Include hidden entries when respecting ignore files
+    #[arg(long = "hidden", requires = "respect_gitignore")]
+    hidden: bool,
+
+    /// Don't descend into directories on other filesystems
+    #[arg(long = "one-file-system", visible_alias = "xdev")]
+    one_file_system: bool,
+
+    /// Output format
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = OutputFormat::Plain,
+        conflicts_with = "printf"
+    )]
+    format: OutputFormat,
+
+    /// Process each directory's contents before the directory itself
+    #[arg(long = "depth", conflicts_with = "respect_gitignore")]
+    depth_first: bool,
+
+    /// Number of hard links, e.g. +1, -3 or 2
+    #[arg(long = "links", value_name = "N", allow_hyphen_values = true)]
+    links: Option<NumCmp>,
+
+    /// Inode number
+    #[arg(long = "inum", value_name = "N")]
+    inum: Option<u64>,
+
+    /// Entries sharing an inode with FILE
+    #[arg(long = "samefile", value_name = "FILE")]
+    samefile: Option<String>,
+
+    /// Stop after N results
+    #[arg(long = "max-results", value_name = "N")]
+    max_results: Option<usize>,
+
+    /// Stop after the first result (same as --max-results 1)
+    #[arg(long = "quit", conflicts_with = "max_results")]
+    quit: bool,
+
+    /// Exclude entries whose name matches the glob PATTERN; excluded directories are not descended into
+    #[arg(long = "exclude", value_name = "PATTERN")]
+    excludes: Vec<Pattern>,
 }
 
 pub fn get_args() -> Result<Config> {
@@ -203,25 +383,113 @@ pub fn get_args() -> Result<Config> {
     Ok(config)
 }
 
-pub fn run(config: Config) -> Result<()> {
-    let walk_dir = |path: &String| {
-        let mut walk_dir = WalkDir::new(path);
+/// Rules applied while walking, so skipped directories are never descended into.
+#[derive(Debug, Clone)]
+struct SkipRules {
+    prunes: Vec<Regex>,
+    excludes: Vec<Pattern>,
+}
+
+impl SkipRules {
+    fn new(config: &Config) -> Self {
+        Self {
+            prunes: config.prunes.clone(),
+            excludes: config.excludes.clone(),
+        }
+    }
+
+    fn skips(&self, name: &OsStr, is_dir: bool, depth: usize) -> bool {
+        let name = name.to_string_lossy();
+        depth > 0
+            && ((is_dir && self.prunes.iter().any(|regex| regex.is_match(&name)))
+                || self.excludes.iter().any(|pattern| pattern.matches(&name)))
+    }
+}
+
+fn walk<'a>(config: &'a Config, path: &str) -> Box<dyn Iterator<Item = Result<Entry>> + 'a> {
+    let skip_rules = SkipRules::new(config);
+    if config.respect_gitignore {
+        let min_depth = config.min_depth.unwrap_or(0);
+        let walker = WalkBuilder::new(path)
+            .hidden(!config.hidden)
+            .require_git(false)
+            .same_file_system(config.one_file_system)
+            .max_depth(config.max_depth)
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !skip_rules.skips(entry.file_name(), is_dir, entry.depth())
+            })
+            .build();
+        Box::new(
+            walker
+                .filter(move |entry| entry.as_ref().map_or(true, |e| e.depth() >= min_depth))
+                .map(|entry| Ok(entry?.into())),
+        )
+    } else {
+        let mut walk_dir = WalkDir::new(path)
+            .same_file_system(config.one_file_system)
+            .contents_first(config.depth_first);
         if let Some(depth) = config.min_depth {
             walk_dir = walk_dir.min_depth(depth);
         }
         if let Some(depth) = config.max_depth {
             walk_dir = walk_dir.max_depth(depth);
         }
-        walk_dir
+        Box::new(
+            walk_dir
+                .into_iter()
+                .filter_entry(move |entry| {
+                    !skip_rules.skips(entry.file_name(), entry.file_type().is_dir(), entry.depth())
+                })
+                .map(|entry| Ok(entry?.into())),
+        )
+    }
+}
+
+fn entry_json(entry: &Entry) -> Result<serde_json::Value> {
+    let metadata = entry.metadata()?;
+    let file_type = entry.file_type();
+    let entry_type = if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_file() {
+        "file"
+    } else {
+        "other"
     };
-    let name_filter = |entry: &DirEntry| {
+    let mtime: DateTime<Local> = metadata.modified()?.into();
+    Ok(json!({
+        "path": entry.path().to_string_lossy(),
+        "type": entry_type,
+        "size": metadata.size(),
+        "mtime": mtime.to_rfc3339(),
+        "permissions": format!("{:o}", metadata.mode() & 0o7777),
+    }))
+}
+
+fn print_entry(config: &Config, entry: &Entry) -> Result<()> {
+    if let Some(format) = &config.printf {
+        print!("{}", format.render(entry)?);
+        return Ok(());
+    }
+    match config.format {
+        OutputFormat::Plain => println!("{}", entry.path().display()),
+        OutputFormat::Null => print!("{}\0", entry.path().display()),
+        OutputFormat::Json => println!("{}", entry_json(entry)?),
+    }
+    Ok(())
+}
+
+pub fn run(config: Config) -> Result<()> {
+    let name_filter = |entry: &Entry| {
         config.names.is_empty()
             || config
                 .names
                 .iter()
                 .any(|regex| regex.is_match(&entry.file_name().to_string_lossy()))
     };
-    let entry_type_filter = |entry: &DirEntry| {
+    let entry_type_filter = |entry: &Entry| {
         let file_type = entry.file_type();
         config.entry_types.is_empty()
             || config
@@ -233,33 +501,66 @@ pub fn run(config: Config) -> Result<()> {
                     EntryType::Link => file_type.is_symlink(),
                 })
     };
-    let file_size_filter = |entry: &DirEntry| match &config.size_type {
+    let file_size_filter = |entry: &Entry| match &config.size_type {
         Some(size_type) => {
             let metadata = entry.metadata().unwrap();
-            let size = metadata.size();
-            match size_type.cmp_flag {
-                CmpFlag::Plus => size > size_type.size,
-                CmpFlag::Minus => size < size_type.size,
-                CmpFlag::None => size == size_type.size,
-            }
+            size_type.matches(metadata.size(), config.size_exact_bytes)
         }
         None => true,
     };
-    for path in config.paths {
-        walk_dir(&path)
-            .into_iter()
-            .filter_map(|entry| match entry {
-                Err(e) => {
-                    eprintln!("{e}");
-                    None
-                }
-                Ok(entry) => Some(entry),
-            })
-            .filter(name_filter)
-            .filter(entry_type_filter)
-            .filter(file_size_filter)
-            .map(|entry| format!("{}", entry.path().display()))
-            .for_each(|path| println!("{path}"));
-    }
+    let samefile = config
+        .samefile
+        .as_ref()
+        .map(|file| {
+            fs::symlink_metadata(file)
+                .map(|metadata| (metadata.dev(), metadata.ino()))
+                .map_err(|e| anyhow!("{file}: {e}"))
+        })
+        .transpose()?;
+    let inode_filter = |entry: &Entry| {
+        if config.links.is_none() && config.inum.is_none() && samefile.is_none() {
+            return true;
+        }
+        match entry.metadata() {
+            Ok(metadata) => {
+                config
+                    .links
+                    .as_ref()
+                    .is_none_or(|links| links.cmp_flag.matches(metadata.nlink(), links.value))
+                    && config.inum.is_none_or(|inum| metadata.ino() == inum)
+                    && samefile.is_none_or(|id| (metadata.dev(), metadata.ino()) == id)
+            }
+            Err(e) => {
+                eprintln!("{}: {e}", entry.path().display());
+                false
+            }
+        }
+    };
+    let max_results = if config.quit {
+        Some(1)
+    } else {
+        config.max_results
+    };
+    config
+        .paths
+        .iter()
+        .flat_map(|path| walk(&config, path))
+        .filter_map(|entry| match entry {
+            Err(e) => {
+                eprintln!("{e}");
+                None
+            }
+            Ok(entry) => Some(entry),
+        })
+        .filter(name_filter)
+        .filter(entry_type_filter)
+        .filter(file_size_filter)
+        .filter(inode_filter)
+        .take(max_results.unwrap_or(usize::MAX))
+        .for_each(|entry| {
+            if let Err(e) = print_entry(&config, &entry) {
+                eprintln!("{}: {e}", entry.path().display());
+            }
+        });
     Ok(())
 }
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::{os::unix::fs::MetadataExt, str::FromStr};
+
+use crate::entry::Entry;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Directive {
+    Literal(String),
+    Path,
+    Name,
+    Dir,
+    Size,
+    Depth,
+    Mode,
+    Type,
+    MTime(char),
+}
+
+/// Parsed `--printf` format, e.g. `%p %s\n`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    directives: Vec<Directive>,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = vec![];
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => literal.push('\n'),
+                    Some('t') => literal.push('\t'),
+                    Some('0') => literal.push('\0'),
+                    Some('\\') => literal.push('\\'),
+                    Some(c) => return Err(format!("unrecognized escape '\\{c}'")),
+                    None => literal.push('\\'),
+                },
+                '%' => {
+                    let directive = match chars.next() {
+                        Some('%') => {
+                            literal.push('%');
+                            continue;
+                        }
+                        Some('p') => Directive::Path,
+                        Some('f') => Directive::Name,
+                        Some('h') => Directive::Dir,
+                        Some('s') => Directive::Size,
+                        Some('d') => Directive::Depth,
+                        Some('m') => Directive::Mode,
+                        Some('y') => Directive::Type,
+                        Some('T') => match chars.next() {
+                            Some(k @ ('Y' | 'm' | 'd' | 'H' | 'M' | 'S')) => Directive::MTime(k),
+                            Some(k) => return Err(format!("unrecognized time field '%T{k}'")),
+                            None => return Err("missing time field after '%T'".to_string()),
+                        },
+                        Some(c) => return Err(format!("unrecognized directive '%{c}'")),
+                        None => return Err("missing directive after '%'".to_string()),
+                    };
+                    if !literal.is_empty() {
+                        directives.push(Directive::Literal(std::mem::take(&mut literal)));
+                    }
+                    directives.push(directive);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            directives.push(Directive::Literal(literal));
+        }
+        Ok(Self { directives })
+    }
+}
+
+impl Format {
+    /// Render the format for a single entry.
+    pub fn render(&self, entry: &Entry) -> Result<String> {
+        let mut out = String::new();
+        for directive in &self.directives {
+            match directive {
+                Directive::Literal(s) => out.push_str(s),
+                Directive::Path => out.push_str(&entry.path().to_string_lossy()),
+                Directive::Name => out.push_str(&entry.file_name().to_string_lossy()),
+                Directive::Dir => out.push_str(
+                    &entry
+                        .path()
+                        .parent()
+                        .map(|p| p.to_string_lossy().into_owned())
+                        .filter(|p| !p.is_empty())
+                        .unwrap_or_else(|| ".".to_string()),
+                ),
+                Directive::Size => out.push_str(&entry.metadata()?.size().to_string()),
+                Directive::Depth => out.push_str(&entry.depth().to_string()),
+                Directive::Mode => {
+                    out.push_str(&format!("{:o}", entry.metadata()?.mode() & 0o7777))
+                }
+                Directive::Type => {
+                    let file_type = entry.file_type();
+                    out.push(if file_type.is_dir() {
+                        'd'
+                    } else if file_type.is_symlink() {
+                        'l'
+                    } else {
+                        'f'
+                    })
+                }
+                Directive::MTime(k) => {
+                    let mtime: DateTime<Local> = entry.metadata()?.modified()?.into();
+                    out.push_str(&mtime.format(&format!("%{k}")).to_string());
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let format: Format = "%p %s\\n".parse().unwrap();
+        assert_eq!(
+            format.directives,
+            vec![
+                Directive::Path,
+                Directive::Literal(" ".to_string()),
+                Directive::Size,
+                Directive::Literal("\n".to_string()),
+            ]
+        );
+
+        let format: Format = "%TY-%Tm-%Td 100%%".parse().unwrap();
+        assert_eq!(
+            format.directives,
+            vec![
+                Directive::MTime('Y'),
+                Directive::Literal("-".to_string()),
+                Directive::MTime('m'),
+                Directive::Literal("-".to_string()),
+                Directive::MTime('d'),
+                Directive::Literal(" 100%".to_string()),
+            ]
+        );
+
+        assert!("%z".parse::<Format>().is_err());
+        assert!("%Tq".parse::<Format>().is_err());
+        assert!("%".parse::<Format>().is_err());
+    }
+}
@@ -4,6 +4,7 @@ use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
 use std::{borrow::Cow, fs, path::Path};
+use tempfile::TempDir;
 
 const PRG: &str = "findr";
 
@@ -60,14 +61,14 @@ fn dies_bad_type() -> Result<()> {
 
 // --------------------------------------------------
 #[cfg(windows)]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Owned(format!("{}.windows", expected_file))
     format!("{}.windows", expected_file).into()
 }
 
 // --------------------------------------------------
 #[cfg(not(windows))]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Borrowed(expected_file)
     expected_file.into()
 }
@@ -404,7 +405,7 @@ fn size_p10c() -> Result<()> {
 #[test]
 fn size_m1k() -> Result<()> {
     run(
-        &["tests/inputs", "--size", "-1k"],
+        &["tests/inputs", "--size", "-1k", "--size-exact-bytes"],
         "tests/expected/size_m1k.txt",
     )
 }
@@ -413,7 +414,364 @@ fn size_m1k() -> Result<()> {
 #[test]
 fn size_p1k() -> Result<()> {
     run(
-        &["tests/inputs", "--size", "+1k"],
+        &["tests/inputs", "--size", "+1k", "--size-exact-bytes"],
         "tests/expected/size_p1k.txt",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn prune_b() -> Result<()> {
+    run(
+        &["tests/inputs", "--prune", "b"],
+        "tests/expected/prune_b.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn prune_b_e() -> Result<()> {
+    run(
+        &["tests/inputs", "--prune", "b", "--prune", "e"],
+        "tests/expected/prune_b_e.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn printf_name_path_a() -> Result<()> {
+    run(
+        &["tests/inputs/a", "--printf", "%f\\n"],
+        "tests/expected/printf_name_path_a.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn printf_type_f_path_a() -> Result<()> {
+    run(
+        &["tests/inputs/a", "-t", "f", "--printf", "%y %f %d\\n"],
+        "tests/expected/printf_type_f_path_a.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_printf() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--printf", "%q"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unrecognized directive '%q'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn gitignore_tree() -> Result<TempDir> {
+    let dir = tempfile::tempdir()?;
+    fs::create_dir(dir.path().join("build"))?;
+    fs::create_dir(dir.path().join("sub"))?;
+    for file in ["a.txt", "b.log", ".hidden", "build/x.o", "sub/c.txt"] {
+        fs::write(dir.path().join(file), "")?;
+    }
+    fs::write(dir.path().join(".gitignore"), "*.log\nbuild/\n")?;
+    Ok(dir)
+}
+
+// --------------------------------------------------
+fn run_names(args: &[&str], expected: &[&str]) -> Result<()> {
+    let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
+    let out = cmd.get_output();
+    let stdout = String::from_utf8(out.stdout.clone())?;
+    let mut lines: Vec<&str> = stdout.split('\n').filter(|s| !s.is_empty()).collect();
+    lines.sort();
+
+    assert_eq!(lines, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn respect_gitignore() -> Result<()> {
+    let dir = gitignore_tree()?;
+    let path = dir.path().to_str().unwrap();
+    run_names(
+        &[
+            path,
+            "--respect-gitignore",
+            "--printf",
+            "%f\\n",
+            "--mindepth",
+            "1",
+        ],
+        &["a.txt", "c.txt", "sub"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn respect_gitignore_hidden() -> Result<()> {
+    let dir = gitignore_tree()?;
+    let path = dir.path().to_str().unwrap();
+    run_names(
+        &[
+            path,
+            "--respect-gitignore",
+            "--hidden",
+            "--printf",
+            "%f\\n",
+            "--mindepth",
+            "1",
+        ],
+        &[".gitignore", ".hidden", "a.txt", "c.txt", "sub"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn one_file_system() -> Result<()> {
+    run(
+        &["tests/inputs", "--one-file-system"],
+        "tests/expected/path1.txt",
+    )?;
+    run(&["tests/inputs", "--xdev"], "tests/expected/path1.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn format_null() -> Result<()> {
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["tests/inputs/a", "--format", "null"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    assert!(stdout.ends_with('\0'));
+    let mut paths: Vec<&str> = stdout.split('\0').filter(|s| !s.is_empty()).collect();
+    paths.sort();
+    let contents = fs::read_to_string(format_file_name("tests/expected/path_a.txt").as_ref())?;
+    let mut expected: Vec<&str> = contents.split('\n').filter(|s| !s.is_empty()).collect();
+    expected.sort();
+    assert_eq!(paths, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(not(windows))]
+fn format_json() -> Result<()> {
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["tests/inputs/a/b", "--format", "json", "-t", "f"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let mut records: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    records.sort_by_key(|record| record["path"].as_str().unwrap().to_string());
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["path"], "tests/inputs/a/b/b.csv");
+    assert_eq!(records[0]["type"], "file");
+    assert_eq!(records[0]["size"], 2);
+    assert_eq!(records[1]["path"], "tests/inputs/a/b/c/c.mp3");
+    assert!(records[1]["mtime"].is_string());
+    assert!(records[1]["permissions"].is_string());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_format_with_printf() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--format", "json", "--printf", "%p"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn depth_first() -> Result<()> {
+    run(&["tests/inputs", "--depth"], "tests/expected/path1.txt")?;
+
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--depth"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let prefix = format!("{line}/");
+        assert!(!lines[i + 1..].iter().any(|l| l.starts_with(&prefix)));
+    }
+    assert_eq!(lines.last(), Some(&"tests/inputs"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(not(windows))]
+fn hard_link_tree() -> Result<TempDir> {
+    let dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("a.txt"), "a")?;
+    fs::hard_link(dir.path().join("a.txt"), dir.path().join("b.txt"))?;
+    fs::write(dir.path().join("c.txt"), "c")?;
+    Ok(dir)
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(not(windows))]
+fn links() -> Result<()> {
+    let dir = hard_link_tree()?;
+    let path = dir.path().to_str().unwrap();
+    run_names(
+        &[path, "-t", "f", "--links", "2", "--printf", "%f\\n"],
+        &["a.txt", "b.txt"],
+    )?;
+    run_names(
+        &[path, "-t", "f", "--links", "+1", "--printf", "%f\\n"],
+        &["a.txt", "b.txt"],
+    )?;
+    run_names(
+        &[path, "-t", "f", "--links", "-2", "--printf", "%f\\n"],
+        &["c.txt"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(not(windows))]
+fn inum_samefile() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = hard_link_tree()?;
+    let path = dir.path().to_str().unwrap();
+    let inum = fs::metadata(dir.path().join("b.txt"))?.ino().to_string();
+    run_names(
+        &[path, "--inum", &inum, "--printf", "%f\\n"],
+        &["a.txt", "b.txt"],
+    )?;
+    let samefile = dir.path().join("a.txt");
+    run_names(
+        &[
+            path,
+            "--samefile",
+            samefile.to_str().unwrap(),
+            "--printf",
+            "%f\\n",
+        ],
+        &["a.txt", "b.txt"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_samefile() -> Result<()> {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--samefile", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("{bad}: ")));
+    Ok(())
+}
+
+// --------------------------------------------------
+fn count_lines(args: &[&str]) -> Result<usize> {
+    let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    Ok(stdout.lines().count())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_results() -> Result<()> {
+    assert_eq!(count_lines(&["tests/inputs", "--max-results", "3"])?, 3);
+    assert_eq!(
+        count_lines(&["tests/inputs/a", "tests/inputs/d", "--max-results", "8"])?,
+        8
+    );
+    assert_eq!(count_lines(&["tests/inputs", "--max-results", "100"])?, 16);
+    assert_eq!(count_lines(&["tests/inputs", "--max-results", "0"])?, 0);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quit() -> Result<()> {
+    assert_eq!(count_lines(&["tests/inputs", "--quit", "-t", "f"])?, 1);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn exclude_csv() -> Result<()> {
+    run(
+        &["tests/inputs", "--exclude", "*.csv"],
+        "tests/expected/exclude_csv.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn exclude_d_c() -> Result<()> {
+    run(
+        &["tests/inputs", "--exclude", "d", "--exclude", "[c]"],
+        "tests/expected/exclude_d_c.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_exclude() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--exclude", "[a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("error: invalid value '[a'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn size_rounded_1k() -> Result<()> {
+    run(
+        &["tests/inputs", "-t", "f", "--size", "1k"],
+        "tests/expected/size_rounded_1k.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn size_rounded_m1k() -> Result<()> {
+    run(
+        &["tests/inputs", "-t", "f", "--size", "-1k"],
+        "tests/expected/size_rounded_m1k.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn size_decimal_units() -> Result<()> {
+    run(
+        &["tests/inputs", "-t", "f", "--size", "1kB"],
+        "tests/expected/size_rounded_1k.txt",
+    )?;
+    run(
+        &["tests/inputs", "-t", "f", "--size", "1KiB"],
+        "tests/expected/size_rounded_1k.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_size_unit() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--size", "1kb"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unit 'kb' is invalid"));
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use crate::matcher::Matcher;
+use anyhow::Result;
+
+/// The SGR sequences coloring each part of the output, as grep sets them
+/// with `GREP_COLORS`. An empty sequence leaves that part uncolored.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Colors {
+    matched: String,
+    file: String,
+    line_num: String,
+    byte_offset: String,
+    separator: String,
+    /// Whether to clear to the end of the line after starting and ending a
+    /// color, so the background color does not spill over
+    erase: bool,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            matched: "01;31".to_string(),
+            file: "35".to_string(),
+            line_num: "32".to_string(),
+            byte_offset: "32".to_string(),
+            separator: "36".to_string(),
+            erase: true,
+        }
+    }
+}
+
+impl Colors {
+    /// Colors that leave everything as it is.
+    pub fn none() -> Self {
+        Self {
+            matched: String::new(),
+            file: String::new(),
+            line_num: String::new(),
+            byte_offset: String::new(),
+            separator: String::new(),
+            erase: false,
+        }
+    }
+
+    /// The default colors, changed by a `GREP_COLORS` value such as
+    /// `ms=01;32:fn=34:ne`. Unknown capabilities are ignored.
+    pub fn from_grep_colors(value: &str) -> Self {
+        let mut colors = Self::default();
+        for cap in value.split(':') {
+            let (name, sgr) = cap.split_once('=').unwrap_or((cap, ""));
+            let sgr = sgr.to_string();
+            match name {
+                "mt" | "ms" => colors.matched = sgr,
+                "fn" => colors.file = sgr,
+                "ln" => colors.line_num = sgr,
+                "bn" => colors.byte_offset = sgr,
+                "se" => colors.separator = sgr,
+                "ne" => colors.erase = false,
+                _ => {}
+            }
+        }
+        colors
+    }
+
+    fn paint(&self, sgr: &str, text: &str) -> String {
+        if sgr.is_empty() {
+            return text.to_string();
+        }
+        let erase = if self.erase { "\x1b[K" } else { "" };
+        format!("\x1b[{sgr}m{erase}{text}\x1b[m{erase}")
+    }
+
+    pub fn file(&self, name: &str) -> String {
+        self.paint(&self.file, name)
+    }
+
+    pub fn line_num(&self, line_num: usize) -> String {
+        self.paint(&self.line_num, &line_num.to_string())
+    }
+
+    pub fn byte_offset(&self, offset: usize) -> String {
+        self.paint(&self.byte_offset, &offset.to_string())
+    }
+
+    pub fn separator(&self, separator: &str) -> String {
+        self.paint(&self.separator, separator)
+    }
+
+    /// Color what `pattern` matches in `line`, up to its newline, so that
+    /// colors end on the line they start on.
+    pub fn matches(&self, pattern: &Matcher, line: &str) -> Result<String> {
+        if self.matched.is_empty() {
+            return Ok(line.to_string());
+        }
+        let text_len = line.strip_suffix('\n').unwrap_or(line).len();
+        let mut result = String::new();
+        let mut pos = 0;
+        for found in pattern.find_iter(line)? {
+            let end = found.end.min(text_len);
+            if found.start >= end {
+                continue;
+            }
+            result += &line[pos..found.start];
+            result += &self.paint(&self.matched, &line[found.start..end]);
+            pos = end;
+        }
+        Ok(result + &line[pos..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Colors;
+    use crate::matcher::Matcher;
+    use regex::Regex;
+
+    #[test]
+    fn test_from_grep_colors() {
+        assert_eq!(Colors::from_grep_colors(""), Colors::default());
+        let colors = Colors::from_grep_colors("ms=01;32:fn=:ne:xx=1");
+        assert_eq!(colors.matched, "01;32");
+        assert_eq!(colors.file, "");
+        assert_eq!(colors.line_num, "32");
+        assert!(!colors.erase);
+        assert_eq!(Colors::from_grep_colors("mt=4").matched, "4");
+    }
+
+    #[test]
+    fn test_paint() {
+        let colors = Colors::default();
+        assert_eq!(colors.file("a.txt"), "\x1b[35m\x1b[Ka.txt\x1b[m\x1b[K");
+        assert_eq!(Colors::from_grep_colors("fn=").file("a.txt"), "a.txt");
+        assert_eq!(
+            Colors::from_grep_colors("ne").line_num(3),
+            "\x1b[32m3\x1b[m"
+        );
+        assert_eq!(Colors::none().separator(":"), ":");
+    }
+
+    #[test]
+    fn test_matches() {
+        let pattern = Matcher::Regex(Regex::new("o+|x*").unwrap());
+        let colors = Colors::from_grep_colors("ne");
+        assert_eq!(
+            colors.matches(&pattern, "foo bo\n").unwrap(),
+            "f\x1b[01;31moo\x1b[m b\x1b[01;31mo\x1b[m\n"
+        );
+        assert_eq!(Colors::none().matches(&pattern, "foo\n").unwrap(), "foo\n");
+
+        // The newline a match takes in stays uncolored
+        let pattern = Matcher::Regex(Regex::new("^(?:foo)\n?$").unwrap());
+        assert_eq!(
+            colors.matches(&pattern, "foo\n").unwrap(),
+            "\x1b[01;31mfoo\x1b[m\n"
+        );
+        let pattern = Matcher::Regex(Regex::new("\n").unwrap());
+        assert_eq!(colors.matches(&pattern, "foo\n").unwrap(), "foo\n");
+    }
+}
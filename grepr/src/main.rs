@@ -1,12 +1,20 @@
+mod colors;
+mod matcher;
+
 use std::{
+    env,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader, IsTerminal},
 };
 
 use anyhow::{Error, Result};
-use clap::{command, Parser};
-use regex::{Regex, RegexBuilder};
-use walkdir::WalkDir;
+use clap::{Parser, ValueEnum};
+use colors::Colors;
+use glob::Pattern;
+use ignore::{DirEntry, WalkBuilder};
+use matcher::Matcher;
+use memmap2::Mmap;
+use regex::{bytes, RegexBuilder};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
@@ -20,58 +28,423 @@ struct Args {
     #[arg(short, long, help = "Recursive search")]
     recursive: bool,
 
+    #[arg(
+        long = "max-depth",
+        value_name = "NUM",
+        help = "Descend at most NUM directories below the command line arguments",
+        requires = "recursive"
+    )]
+    max_depth: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Search only files whose names match GLOB",
+        value_parser = Pattern::new
+    )]
+    include: Vec<Pattern>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip files whose names match GLOB",
+        value_parser = Pattern::new
+    )]
+    exclude: Vec<Pattern>,
+
+    #[arg(
+        long = "exclude-dir",
+        value_name = "GLOB",
+        help = "Skip directories whose names match GLOB when recursing",
+        value_parser = Pattern::new
+    )]
+    exclude_dir: Vec<Pattern>,
+
+    #[arg(
+        long = "no-ignore",
+        help = "Search files ignored by .gitignore and .ignore files when recursing"
+    )]
+    no_ignore: bool,
+
+    #[arg(long, help = "Search hidden files and directories when recursing")]
+    hidden: bool,
+
     #[arg(short, long, help = "Count occurences")]
     count: bool,
 
+    #[arg(
+        long = "count-matches",
+        help = "Count every match rather than matching lines",
+        conflicts_with_all = ["count", "invert_match", "files_with_matches", "files_without_match"]
+    )]
+    count_matches: bool,
+
     #[arg(short = 'v', long = "invert-match", help = "Invert match")]
     invert_match: bool,
 
     #[arg(short, long, help = "Case-insensitive")]
     insensitive: bool,
+
+    #[arg(
+        long,
+        value_name = "ENGINE",
+        help = "Regex engine: regex, or fancy for look-around and backreferences",
+        value_enum,
+        default_value = "regex"
+    )]
+    engine: Engine,
+
+    #[arg(short = 'n', long = "line-number", help = "Print line numbers")]
+    line_number: bool,
+
+    #[arg(
+        short = 'b',
+        long = "byte-offset",
+        help = "Print byte offsets of lines"
+    )]
+    byte_offset: bool,
+
+    #[arg(short = 'w', long = "word-regexp", help = "Match only whole words")]
+    word_regexp: bool,
+
+    #[arg(short = 'x', long = "line-regexp", help = "Match only whole lines")]
+    line_regexp: bool,
+
+    #[arg(
+        short = 'l',
+        long = "files-with-matches",
+        help = "Print only the names of files with matches",
+        conflicts_with_all = ["count", "files_without_match"]
+    )]
+    files_with_matches: bool,
+
+    #[arg(
+        short = 'L',
+        long = "files-without-match",
+        help = "Print only the names of files without matches",
+        conflicts_with = "count"
+    )]
+    files_without_match: bool,
+
+    #[arg(
+        short = 'm',
+        long = "max-count",
+        value_name = "NUM",
+        help = "Stop reading a file after NUM selected lines"
+    )]
+    max_count: Option<usize>,
+
+    #[arg(
+        short = 's',
+        long = "no-messages",
+        help = "Suppress messages about unreadable files"
+    )]
+    no_messages: bool,
+
+    #[arg(
+        long = "binary-files",
+        value_name = "TYPE",
+        help = "How to search files with NUL bytes: binary, text or without-match",
+        value_enum,
+        default_value = "binary"
+    )]
+    binary_files: BinaryFiles,
+
+    #[arg(
+        short = 'a',
+        long = "text",
+        help = "Search binary files as text, like --binary-files=text"
+    )]
+    text: bool,
+
+    #[arg(
+        long,
+        help = "Search regular files by mapping them into memory, faster for large files; \
+                a file truncated while it is searched kills grepr with SIGBUS"
+    )]
+    mmap: bool,
+
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        help = "Highlight matches, file names and line numbers: auto, always or never",
+        value_enum,
+        default_value = "auto",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "auto"
+    )]
+    color: ColorWhen,
+}
+
+/// Which regex engine compiles the pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Engine {
+    /// Linear time, without look-around or backreferences
+    Regex,
+    /// Backtracking, with look-around and backreferences
+    Fancy,
+}
+
+/// What to do with files that have NUL bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum BinaryFiles {
+    /// Only say whether they match
+    Binary,
+    /// Print matching lines as for any other file
+    Text,
+    /// Assume they do not match
+    WithoutMatch,
+}
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColorWhen {
+    /// When stdout is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+/// Which files and directories to search.
+#[derive(Clone, Debug, Default)]
+struct Filter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    exclude_dir: Vec<Pattern>,
+    /// Search what ignore files list
+    no_ignore: bool,
+    /// Search hidden files and directories
+    hidden: bool,
+}
+
+impl Filter {
+    fn allows(&self, entry: &DirEntry) -> bool {
+        let name = entry.file_name().to_string_lossy();
+        let matches = |globs: &[Pattern]| globs.iter().any(|glob| glob.matches(&name));
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            // Directories named on the command line are always searched
+            entry.depth() == 0 || !matches(&self.exclude_dir)
+        } else {
+            (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+        }
+    }
+}
+
+/// A line selected by the pattern, and where it was found.
+#[derive(Debug, PartialEq, Eq)]
+struct Match {
+    /// 1-based line number
+    line_num: usize,
+    /// Offset of the start of the line
+    offset: usize,
+    line: String,
+}
+
+/// Wrap `pattern` so that it only matches whole lines, or else whole words.
+fn anchor_pattern(pattern: &str, line_regexp: bool, word_regexp: bool) -> String {
+    if line_regexp {
+        // Lines are matched with their newline
+        format!("^(?:{})\n?$", pattern)
+    } else if word_regexp {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern.to_string()
+    }
 }
 
-fn find_files(paths: &[String], recursive: bool) -> Vec<Result<String>> {
-    if paths.len() == 1 && paths[0] == "-" {
-        return vec![Ok("-".to_string())];
+/// The colors to print with, given `--color` and the environment.
+fn colors(when: ColorWhen) -> Colors {
+    let enabled = match when {
+        ColorWhen::Always => true,
+        ColorWhen::Never => false,
+        ColorWhen::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|val| val.is_empty())
+        }
+    };
+    match env::var("GREP_COLORS") {
+        _ if !enabled => Colors::none(),
+        Ok(value) => Colors::from_grep_colors(&value),
+        Err(_) => Colors::default(),
     }
+}
+
+/// The files under `paths`, which are only searched below when `recursive`,
+/// down to `max_depth` if given.
+fn find_files(
+    paths: &[String],
+    recursive: bool,
+    max_depth: Option<usize>,
+    filter: &Filter,
+) -> Vec<Result<String>> {
     paths
         .iter()
-        .flat_map(|path| WalkDir::new(path).max_depth(recursive as usize).into_iter())
-        .map(|e| match e {
-            Ok(e) => {
-                if !recursive && e.file_type().is_dir() {
-                    Err(Error::msg(format!(
-                        "{} is a directory",
-                        e.path().to_string_lossy()
-                    )))
-                } else {
-                    Ok(e)
-                }
+        .flat_map(|path| {
+            // The walker would take - for a file with no type and drop it
+            if path == "-" {
+                return vec![Ok("-".to_string())];
             }
-            Err(err) => Err(Error::new(err)),
+            let filter = filter.clone();
+            // Ignore files and hidden names never hide paths given as arguments
+            WalkBuilder::new(path)
+                .standard_filters(!filter.no_ignore)
+                .hidden(!filter.hidden)
+                .require_git(false)
+                .max_depth(if recursive { max_depth } else { Some(0) })
+                .filter_entry(move |e| filter.allows(e))
+                .build()
+                .map(|e| match e {
+                    Ok(e) => {
+                        if !recursive && e.file_type().is_some_and(|t| t.is_dir()) {
+                            Err(Error::msg(format!(
+                                "{} is a directory",
+                                e.path().to_string_lossy()
+                            )))
+                        } else {
+                            Ok(e)
+                        }
+                    }
+                    Err(err) => Err(Error::new(err)),
+                })
+                .filter(|e| {
+                    e.as_ref()
+                        .map_or(true, |e| e.file_type().is_some_and(|t| t.is_file()))
+                })
+                .map(|e| e.map(|e| e.path().to_string_lossy().into_owned()))
+                .collect()
         })
-        .filter(|e| e.as_ref().map_or(true, |e| e.file_type().is_file()))
-        .map(|e| e.map(|e| e.path().to_string_lossy().into_owned()))
         .collect::<Vec<_>>()
 }
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(std::io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+/// What lines are searched in: a reader, or a whole file mapped into memory.
+enum Input {
+    Reader(Box<dyn BufRead>),
+    Mapped(Mmap),
+}
+
+/// Open `filename`, mapping it into memory with `mmap` if it is a regular
+/// file.
+fn open(filename: &str, mmap: bool) -> Result<Input> {
+    if filename == "-" {
+        return Ok(Input::Reader(Box::new(BufReader::new(std::io::stdin()))));
+    }
+    let file = File::open(filename)?;
+    let metadata = file.metadata()?;
+    if mmap && metadata.is_file() {
+        // SAFETY: the map is only read while the file is open; if another
+        // process truncates the file meanwhile, reading past its end raises
+        // SIGBUS, as --mmap warns
+        return Ok(Input::Mapped(unsafe { Mmap::map(&file)? }));
+    }
+    Ok(Input::Reader(Box::new(BufReader::new(file))))
+}
+
+/// Whether the start of `file` has a NUL byte, which text never has.
+fn is_binary<T: BufRead>(file: &mut T) -> Result<bool> {
+    Ok(file.fill_buf()?.contains(&0))
+}
+
+/// The start of the line that `pos` is on.
+fn line_start(buf: &[u8], pos: usize) -> usize {
+    buf[..pos]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1)
+}
+
+/// The end of the line starting at `start`, after its newline.
+fn line_end(buf: &[u8], start: usize) -> usize {
+    buf[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(buf.len(), |i| start + i + 1)
+}
+
+/// Find the lines of `buf` selected by `pattern`, like `find_lines`, but by
+/// searching the whole buffer and only looking for lines around matches.
+/// `pattern` must be multi-line, so that `^` and `$` match at lines.
+fn find_lines_in(
+    buf: &[u8],
+    pattern: &bytes::Regex,
+    invert_match: bool,
+    limit: Option<usize>,
+) -> Vec<Match> {
+    let mut result = Vec::new();
+    let full = |result: &Vec<Match>| limit.is_some_and(|limit| result.len() >= limit);
+    let push = |result: &mut Vec<Match>, line_num, start, end| {
+        result.push(Match {
+            line_num,
+            offset: start,
+            line: String::from_utf8_lossy(&buf[start..end]).into_owned(),
+        })
+    };
+    let mut line_num = 1;
+    let mut pos = 0;
+    while pos < buf.len() && !full(&result) {
+        // The next line with a match, or the end; past the last newline
+        // there are no more lines
+        let (next, matched) = match pattern.find_at(buf, pos) {
+            Some(m) if line_start(buf, m.start()) < buf.len() => {
+                let start = line_start(buf, m.start());
+                let end = line_end(buf, start);
+                // A match running into the next line only counts if there is
+                // one within the line
+                (start, m.end() <= end || pattern.is_match(&buf[start..end]))
+            }
+            _ => (buf.len(), false),
+        };
+        if invert_match {
+            while pos < next && !full(&result) {
+                let end = line_end(buf, pos);
+                push(&mut result, line_num, pos, end);
+                line_num += 1;
+                pos = end;
+            }
+            if pos < next {
+                break;
+            }
+        } else {
+            line_num += buf[pos..next].iter().filter(|&&b| b == b'\n').count();
+        }
+        if next == buf.len() {
+            break;
+        }
+        let end = line_end(buf, next);
+        if matched != invert_match && !full(&result) {
+            push(&mut result, line_num, next, end);
+        }
+        line_num += 1;
+        pos = end;
     }
+    result
 }
 
-fn find_lines<T: BufRead>(mut file: T, pattern: &Regex, invert_match: bool) -> Result<Vec<String>> {
+/// Find the lines selected by `pattern`, stopping once there are `limit`.
+fn find_lines<T: BufRead>(
+    mut file: T,
+    pattern: &Matcher,
+    invert_match: bool,
+    limit: Option<usize>,
+) -> Result<Vec<Match>> {
     let mut result = Vec::new();
-    let mut buf = String::new();
-    loop {
-        match file.read_line(&mut buf) {
+    let mut buf = Vec::new();
+    let mut offset = 0;
+    for line_num in 1.. {
+        if limit.is_some_and(|limit| result.len() >= limit) {
+            break;
+        }
+        match file.read_until(b'\n', &mut buf) {
             Ok(0) => break,
-            Ok(_) => {
-                if pattern.is_match(&buf) ^ invert_match {
-                    result.push(buf.clone());
+            Ok(len) => {
+                let line = String::from_utf8_lossy(&buf);
+                if pattern.is_match(&line)? ^ invert_match {
+                    result.push(Match {
+                        line_num,
+                        offset,
+                        line: line.into_owned(),
+                    });
                 }
+                offset += len;
                 buf.clear();
             }
             Err(e) => return Err(Error::new(e)),
@@ -80,45 +453,173 @@ fn find_lines<T: BufRead>(mut file: T, pattern: &Regex, invert_match: bool) -> R
     Ok(result)
 }
 
-fn run(args: Args) -> Result<()> {
-    let pattern = RegexBuilder::new(&args.pattern)
-        .case_insensitive(args.insensitive)
-        .build()
-        .map_err(|_| Error::msg(format!("Invalid pattern \"{}\"", &args.pattern)))?;
-    let entries = find_files(&args.files, args.recursive);
-    for entry in &entries {
-        match entry {
-            Err(e) => eprintln!("{}", e),
-            Ok(filename) => match open(filename) {
-                Err(e) => eprintln!("{}: {}", filename, e),
-                Ok(file) => {
-                    let matches = find_lines(file, &pattern, args.invert_match)?;
-                    if args.count {
-                        if entries.len() > 1 {
-                            println!("{}:{}", filename, matches.len());
-                        } else {
-                            println!("{}", matches.len());
-                        }
-                    } else {
-                        for line in matches {
-                            if entries.len() > 1 {
-                                print!("{}:{}", filename, line);
-                            } else {
-                                print!("{}", line);
-                            }
-                        }
+/// Search the files, returning how many could not be searched.
+fn run(args: Args) -> Result<usize> {
+    let pattern = anchor_pattern(&args.pattern, args.line_regexp, args.word_regexp);
+    let invalid = |hint| Error::msg(format!("Invalid pattern \"{}\"{}", &args.pattern, hint));
+    let pattern = match args.engine {
+        Engine::Regex => Matcher::Regex(
+            RegexBuilder::new(&pattern)
+                .case_insensitive(args.insensitive)
+                .build()
+                .map_err(|e| match e {
+                    regex::Error::Syntax(msg)
+                        if msg.contains("look-around") || msg.contains("backreferences") =>
+                    {
+                        invalid(": look-around and backreferences need --engine=fancy")
                     }
+                    _ => invalid(""),
+                })?,
+        ),
+        Engine::Fancy => {
+            let flags = if args.insensitive { "(?i)" } else { "" };
+            Matcher::Fancy(
+                fancy_regex::Regex::new(&format!("{}{}", flags, pattern))
+                    .map_err(|_| invalid(""))?,
+            )
+        }
+    };
+    // Searching a whole mapped file needs ^ and $ to match at each line
+    let mapped_pattern = match &pattern {
+        Matcher::Regex(re) => Some(
+            bytes::RegexBuilder::new(re.as_str())
+                .case_insensitive(args.insensitive)
+                .multi_line(true)
+                .build()?,
+        ),
+        // The fancy engine only searches text, so mapped files are read by line
+        Matcher::Fancy(_) => None,
+    };
+    let colors = colors(args.color);
+    let binary_files = if args.text {
+        BinaryFiles::Text
+    } else {
+        args.binary_files
+    };
+    let filter = Filter {
+        include: args.include,
+        exclude: args.exclude,
+        exclude_dir: args.exclude_dir,
+        no_ignore: args.no_ignore,
+        hidden: args.hidden,
+    };
+    let entries = find_files(&args.files, args.recursive, args.max_depth, &filter);
+    let multiple = entries.len() > 1;
+    let search = |filename: &str, mut input: Input| -> Result<()> {
+        let name = if filename == "-" {
+            "(standard input)"
+        } else {
+            filename
+        };
+        let binary = binary_files != BinaryFiles::Text
+            && match &mut input {
+                Input::Reader(file) => is_binary(file)?,
+                // As much as a BufReader would have read
+                Input::Mapped(map) => map.iter().take(8192).any(|&b| b == 0),
+            };
+        let names_only = args.files_with_matches || args.files_without_match;
+        let counting = args.count || args.count_matches;
+        let binary_matches = binary && !names_only && !counting;
+        // One match is enough to know which files have them, and
+        // to say that a binary file matches
+        let limit = match args.max_count {
+            Some(max) if names_only || binary_matches => Some(max.min(1)),
+            None if names_only || binary_matches => Some(1),
+            max => max,
+        };
+        let matches = if binary && binary_files == BinaryFiles::WithoutMatch {
+            vec![]
+        } else {
+            match input {
+                Input::Reader(file) => find_lines(file, &pattern, args.invert_match, limit)?,
+                Input::Mapped(map) => match &mapped_pattern {
+                    Some(re) => find_lines_in(&map, re, args.invert_match, limit),
+                    None => find_lines(&map[..], &pattern, args.invert_match, limit)?,
+                },
+            }
+        };
+        if names_only {
+            if matches.is_empty() == args.files_without_match {
+                println!("{}", colors.file(name));
+            }
+        } else if counting {
+            let count = if args.count_matches {
+                // A line selected by an empty match still counts once
+                let mut count = 0;
+                for found in &matches {
+                    let all = pattern.find_iter(&found.line)?;
+                    count += all.iter().filter(|m| !m.is_empty()).count().max(1);
+                }
+                count
+            } else {
+                matches.len()
+            };
+            if multiple {
+                println!(
+                    "{}{}{}",
+                    colors.file(filename),
+                    colors.separator(":"),
+                    count
+                );
+            } else {
+                println!("{}", count);
+            }
+        } else if binary_matches {
+            if !matches.is_empty() {
+                println!("{}: binary file matches", name);
+            }
+        } else {
+            for found in matches {
+                let mut prefix = String::new();
+                if multiple {
+                    prefix += &colors.file(filename);
+                    prefix += &colors.separator(":");
+                }
+                if args.line_number {
+                    prefix += &colors.line_num(found.line_num);
+                    prefix += &colors.separator(":");
+                }
+                if args.byte_offset {
+                    prefix += &colors.byte_offset(found.offset);
+                    prefix += &colors.separator(":");
                 }
-            },
+                // Inverted matches are the lines without any
+                let line = if args.invert_match {
+                    found.line
+                } else {
+                    colors.matches(&pattern, &found.line)?
+                };
+                print!("{}{}", prefix, line);
+            }
+        }
+        Ok(())
+    };
+    let mut errors = 0;
+    for entry in &entries {
+        let result = match entry {
+            Err(e) => Err(e.to_string()),
+            Ok(filename) => open(filename, args.mmap)
+                .and_then(|input| search(filename, input))
+                .map_err(|e| format!("{}: {}", filename, e)),
+        };
+        if let Err(message) = result {
+            errors += 1;
+            if !args.no_messages {
+                eprintln!("{}", message);
+            }
         }
     }
-    Ok(())
+    Ok(errors)
 }
 
 fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    match run(Args::parse()) {
+        Ok(0) => {}
+        Ok(_) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
     }
 }
 
@@ -126,80 +627,216 @@ fn main() {
 mod tests {
     use super::*;
     use rand::{distributions::Alphanumeric, Rng};
+    use regex::Regex;
     use std::io::Cursor;
 
     #[test]
     fn test_find_files() {
-        let files = find_files(&["./tests/inputs/fox.txt".to_string()], false);
+        let files = find_files(
+            &["./tests/inputs/fox.txt".to_string()],
+            false,
+            None,
+            &Filter::default(),
+        );
         assert_eq!(files.len(), 1);
         assert_eq!(
             files[0].as_ref().unwrap().to_owned(),
             "./tests/inputs/fox.txt".to_string()
         );
 
-        let files = find_files(&["./tests/inputs".to_string()], false);
+        let files = find_files(
+            &["./tests/inputs".to_string()],
+            false,
+            None,
+            &Filter::default(),
+        );
         assert_eq!(files.len(), 1);
         if let Err(e) = &files[0] {
             assert!(e.to_string().contains("./tests/inputs is a directory"));
         }
 
-        let files = find_files(&["./tests/inputs".to_string()], true);
+        let files = find_files(
+            &["./tests/inputs".to_string()],
+            true,
+            None,
+            &Filter::default(),
+        );
         let mut files: Vec<_> = files
             .iter()
             .map(|r| r.as_ref().unwrap().replace("\\", "/"))
             .collect();
         files.sort();
-        assert_eq!(files.len(), 4);
+        assert_eq!(files.len(), 7);
         assert_eq!(
             files,
             vec![
+                "./tests/inputs/binary.bin",
                 "./tests/inputs/bustle.txt",
                 "./tests/inputs/empty.txt",
                 "./tests/inputs/fox.txt",
+                "./tests/inputs/nested/deeper/deep.txt",
+                "./tests/inputs/nested/shallow.txt",
                 "./tests/inputs/nobody.txt"
             ]
         );
 
+        let files = find_files(
+            &["./tests/inputs".to_string()],
+            true,
+            Some(1),
+            &Filter::default(),
+        );
+        assert_eq!(files.len(), 5);
+
+        let filter = Filter {
+            include: vec![Pattern::new("*.txt").unwrap()],
+            exclude: vec![Pattern::new("[ef]*").unwrap()],
+            exclude_dir: vec![Pattern::new("nest*").unwrap()],
+            ..Filter::default()
+        };
+        let files = find_files(&["./tests/inputs".to_string()], true, None, &filter);
+        let mut files: Vec<_> = files
+            .iter()
+            .map(|r| r.as_ref().unwrap().replace("\\", "/"))
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec!["./tests/inputs/bustle.txt", "./tests/inputs/nobody.txt"]
+        );
+
         let bad: String = rand::thread_rng()
             .sample_iter(&Alphanumeric)
             .take(7)
             .map(char::from)
             .collect();
 
-        let files = find_files(&[bad], false);
+        let files = find_files(&[bad], false, None, &Filter::default());
         assert_eq!(files.len(), 1);
         assert!(files[0].is_err());
     }
 
+    #[test]
+    fn test_anchor_pattern() {
+        let matches = |pattern: &str, line, word, text: &str| {
+            Regex::new(&anchor_pattern(pattern, line, word))
+                .unwrap()
+                .is_match(text)
+        };
+        assert!(matches("fox", false, false, "foxes\n"));
+        assert!(!matches("fox", false, true, "foxes\n"));
+        assert!(matches("fox", false, true, "a fox.\n"));
+        assert!(matches("a|fox", false, true, "foxes a\n"));
+        assert!(!matches("fox", true, false, "a fox\n"));
+        assert!(matches("a fox", true, false, "a fox\n"));
+        assert!(matches("a fox", true, false, "a fox"));
+        assert!(matches("a|fox", true, true, "fox\n"));
+        assert!(!matches("a|fox", true, true, "a fox\n"));
+        assert!(matches("", true, false, "\n"));
+        assert!(!matches("", true, false, "fox\n"));
+    }
+
+    #[test]
+    fn test_find_lines_in() {
+        let texts: [&[u8]; 4] = [b"Lorem\nIpsum\r\nDOLOR", b"a\n\nb\n", b"", b"\n"];
+        // Lines read one at a time end with their newline, so only a $ at the
+        // end of the text matches the same way
+        let patterns = [
+            "or", "(?i)or", "^", "^I", "$", "R$", "", "\n", "m\nI", "\n\n", "x",
+        ];
+        for text in texts {
+            for pattern in patterns {
+                let re = Matcher::Regex(Regex::new(pattern).unwrap());
+                let bytes_re = bytes::RegexBuilder::new(pattern)
+                    .multi_line(true)
+                    .build()
+                    .unwrap();
+                for invert in [false, true] {
+                    for limit in [None, Some(0), Some(1)] {
+                        assert_eq!(
+                            find_lines_in(text, &bytes_re, invert, limit),
+                            find_lines(Cursor::new(text), &re, invert, limit).unwrap(),
+                            "{:?} in {:?}, invert {}, limit {:?}",
+                            pattern,
+                            String::from_utf8_lossy(text),
+                            invert,
+                            limit
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_lines_fancy() {
+        let text = b"Lorem\nIpsum\r\nDOLOR\nlooks";
+        let re = Matcher::Fancy(fancy_regex::Regex::new(r"(.)\1|(?<=r)e").unwrap());
+        let matches = find_lines(Cursor::new(&text), &re, false, None).unwrap();
+        let lines: Vec<_> = matches.iter().map(|found| found.line_num).collect();
+        assert_eq!(lines, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(&mut Cursor::new(b"text\n")).unwrap());
+        assert!(is_binary(&mut Cursor::new(b"text\0\n")).unwrap());
+        assert!(!is_binary(&mut Cursor::new(b"")).unwrap());
+    }
+
     #[test]
     fn test_find_lines() {
         let text = b"Lorem\nIpsum\r\nDOLOR";
 
         // should match "Lorem"
-        let re1 = Regex::new("or").unwrap();
-        let matches = find_lines(Cursor::new(&text), &re1, false);
+        let re1 = Matcher::Regex(Regex::new("or").unwrap());
+        let matches = find_lines(Cursor::new(&text), &re1, false, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 1);
 
         // should match "Ipsum" and "DOLOR"
-        let matches = find_lines(Cursor::new(&text), &re1, true);
+        let matches = find_lines(Cursor::new(&text), &re1, true, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 2);
 
         // regex which does not distinguish sequence "or" from sequence "OR"
-        let re2 = RegexBuilder::new("or")
-            .case_insensitive(true)
-            .build()
-            .unwrap();
+        let re2 = Matcher::Regex(
+            RegexBuilder::new("or")
+                .case_insensitive(true)
+                .build()
+                .unwrap(),
+        );
 
         // should match "Lorem" and "DOLOR"
-        let matches = find_lines(Cursor::new(&text), &re2, false);
+        let matches = find_lines(Cursor::new(&text), &re2, false, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 2);
 
         // should match "Ipsum"
-        let matches = find_lines(Cursor::new(&text), &re2, true);
+        let matches = find_lines(Cursor::new(&text), &re2, true, None);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 1);
+
+        // records where the lines were found
+        let matches = find_lines(Cursor::new(&text), &re2, false, None);
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Match {
+                    line_num: 1,
+                    offset: 0,
+                    line: "Lorem\n".to_string(),
+                },
+                Match {
+                    line_num: 3,
+                    offset: 13,
+                    line: "DOLOR".to_string(),
+                },
+            ]
+        );
+
+        // should stop after the first match
+        let matches = find_lines(Cursor::new(&text), &re2, false, Some(1));
+        assert_eq!(matches.unwrap().len(), 1);
     }
 }
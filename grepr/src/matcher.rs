@@ -0,0 +1,50 @@
+use anyhow::Result;
+use std::ops::Range;
+
+/// A pattern compiled by one of the regex engines.
+pub enum Matcher {
+    Regex(regex::Regex),
+    /// Supports look-around and backreferences, by backtracking
+    Fancy(fancy_regex::Regex),
+}
+
+impl Matcher {
+    /// Whether the pattern matches `text`. The fancy engine gives up with an
+    /// error when it backtracks too much.
+    pub fn is_match(&self, text: &str) -> Result<bool> {
+        Ok(match self {
+            Matcher::Regex(re) => re.is_match(text),
+            Matcher::Fancy(re) => re.is_match(text)?,
+        })
+    }
+
+    /// Where the pattern matches in `text`.
+    pub fn find_iter(&self, text: &str) -> Result<Vec<Range<usize>>> {
+        Ok(match self {
+            Matcher::Regex(re) => re.find_iter(text).map(|m| m.range()).collect(),
+            Matcher::Fancy(re) => {
+                let mut ranges = Vec::new();
+                for found in re.find_iter(text) {
+                    ranges.push(found?.range());
+                }
+                ranges
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matcher;
+
+    #[test]
+    fn test_matcher() {
+        let re = Matcher::Regex(regex::Regex::new("o+").unwrap());
+        assert!(re.is_match("foo").unwrap());
+        assert_eq!(re.find_iter("foo bo").unwrap(), vec![1..3, 5..6]);
+
+        let fancy = Matcher::Fancy(fancy_regex::Regex::new(r"(o)\1|b(?=o)").unwrap());
+        assert!(!fancy.is_match("fo").unwrap());
+        assert_eq!(fancy.find_iter("foo bo").unwrap(), vec![1..3, 4..5]);
+    }
+}
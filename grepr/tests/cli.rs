@@ -7,6 +7,7 @@ use std::{fs, path::Path};
 use sys_info::os_type;
 
 const PRG: &str = "grepr";
+const BINARY: &str = "tests/inputs/binary.bin";
 const BUSTLE: &str = "tests/inputs/bustle.txt";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
@@ -49,6 +50,46 @@ fn dies_bad_pattern() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_lookaround_hints_engine() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["fox(?= jumps)", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("need --engine=fancy"));
+    Command::cargo_bin(PRG)?
+        .args(["--engine=fancy", "*foo", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(r#"Invalid pattern "*foo""#));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn engine_fancy() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--engine", "fancy", "-n", r"(\w)\1!", NOBODY])
+        .assert()
+        .success()
+        .stdout("4:Don't tell! they'd advertise\u{2014}you know!\r\n");
+    Command::cargo_bin(PRG)?
+        .args(["--engine=fancy", "-i", "--mmap", "-c", "THE(?= )", BUSTLE])
+        .assert()
+        .success()
+        .stdout("3\n");
+    Command::cargo_bin(PRG)?
+        .args(["--engine=fancy", "--color=always", "fox(?= jumps)", FOX])
+        .env("GREP_COLORS", "ne")
+        .assert()
+        .success()
+        .stdout(
+            "The quick brown \x1b[01;31mfox\x1b[m jumps over the lazy dog.\n",
+        );
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn warns_bad_file() -> Result<()> {
@@ -57,6 +98,7 @@ fn warns_bad_file() -> Result<()> {
     Command::cargo_bin(PRG)?
         .args(["foo", &bad])
         .assert()
+        .failure()
         .stderr(predicate::str::is_match(expected)?);
     Ok(())
 }
@@ -168,6 +210,104 @@ fn recursive_insensitive() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn recursive_nested() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-r", "deep well", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{INPUTS_DIR}/nested/deeper/deep.txt:A deep well\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--exclude-dir", "deep*", "well", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{INPUTS_DIR}/nested/shallow.txt\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_depth() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--max-depth", "1", "well", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout("");
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--max-depth=2", "well", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{INPUTS_DIR}/nested/shallow.txt\n"));
+    Command::cargo_bin(PRG)?
+        .args(["--max-depth", "1", "well", INPUTS_DIR])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--recursive"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_skips_hidden_and_ignored() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "hidden dog|ignored dog", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout("");
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--hidden", "hidden dog|ignored dog", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{INPUTS_DIR}/.hidden.txt\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--no-ignore", "hidden dog|ignored dog", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{INPUTS_DIR}/ignored.txt\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn ignore_files_do_not_hide_arguments() -> Result<()> {
+    let ignored = format!("{INPUTS_DIR}/ignored.txt");
+    Command::cargo_bin(PRG)?
+        .args(["dog", &ignored])
+        .assert()
+        .success()
+        .stdout("An ignored dog\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include_exclude() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-rl", "--include", "*dy.txt", "the", INPUTS_DIR])
+        .assert()
+        .success()
+        .stdout(format!("{NOBODY}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-rL", "--exclude=[fn]*", "--exclude=b*", "the", INPUTS_DIR])
+        .arg("--exclude-dir=nested")
+        .assert()
+        .success()
+        .stdout(format!("{EMPTY}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_glob() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--include", "[", "the", INPUTS_DIR])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value '[' for '--include"));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn sensitive_count_capital() -> Result<()> {
@@ -228,6 +368,349 @@ fn insensitive_count_multiple() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn count_matches() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "-i", "the", BUSTLE, FOX])
+        .assert()
+        .success()
+        .stdout(format!("{BUSTLE}:4\n{FOX}:2\n"));
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "o*", FOX])
+        .assert()
+        .success()
+        .stdout("4\n");
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "", FOX])
+        .assert()
+        .success()
+        .stdout("1\n");
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "-c", "the", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn line_number() -> Result<()> {
+    run(
+        &["-n", "the", BUSTLE],
+        "tests/expected/bustle.txt.the.lowercase.line_number",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn byte_offset() -> Result<()> {
+    run(
+        &["--byte-offset", "the", BUSTLE],
+        "tests/expected/bustle.txt.the.lowercase.byte_offset",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_line_number_byte_offset() -> Result<()> {
+    run(
+        &["-bni", "the", BUSTLE, EMPTY, FOX],
+        "tests/expected/all.the.lowercase.insensitive.line_number.byte_offset",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-i", "the", BUSTLE, EMPTY, FOX, NOBODY])
+        .assert()
+        .success()
+        .stdout(format!("{BUSTLE}\n{FOX}\n{NOBODY}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["--files-with-matches", "dog"])
+        .write_stdin("dog\ndog\n")
+        .assert()
+        .success()
+        .stdout("(standard input)\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_without_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-L", "The ", BUSTLE, EMPTY, FOX, NOBODY])
+        .assert()
+        .success()
+        .stdout(format!("{EMPTY}\n{NOBODY}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["--files-without-match", "-v", "dog", FOX])
+        .assert()
+        .success()
+        .stdout(format!("{FOX}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_with_matches_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-c", "dog", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-w", "the|up", BUSTLE])
+        .assert()
+        .success()
+        .stdout("The sweeping up the heart,\n");
+    Command::cargo_bin(PRG)?
+        .args(["--word-regexp", "-i", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout(
+            "The bustle in a house\nThe morning after death\n\
+             The sweeping up the heart,\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-x", "-n", "", BUSTLE])
+        .assert()
+        .success()
+        .stdout("5:\n");
+    Command::cargo_bin(PRG)?
+        .args(["--line-regexp", "And putting love away|love", BUSTLE])
+        .assert()
+        .success()
+        .stdout("And putting love away\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-n", "the", BUSTLE, FOX])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{BUSTLE}:6:The sweeping up the heart,\n\
+            {FOX}:1:The quick brown fox jumps over the lazy dog.\n"
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["--max-count=2", "-c", "-i", "the", BUSTLE, NOBODY])
+        .assert()
+        .success()
+        .stdout(format!("{BUSTLE}:2\n{NOBODY}:2\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-nv", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout("1:The bustle in a house\n");
+    Command::cargo_bin(PRG)?
+        .args(["-m", "0", "-L", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!("{BUSTLE}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["zebra", BINARY, FOX])
+        .assert()
+        .success()
+        .stdout(format!("{BINARY}: binary file matches\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-c", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout("1\n");
+    Command::cargo_bin(PRG)?
+        .args(["-l", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout(format!("{BINARY}\n"));
+    Command::cargo_bin(PRG)?
+        .arg("zebra")
+        .write_stdin("zebra\0\n")
+        .assert()
+        .success()
+        .stdout("(standard input): binary file matches\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_text() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-a", "-n", "zebra|data", BINARY])
+        .assert()
+        .success()
+        .stdout("1:Binary\0data \u{FFFD}\n2:with a zebra\n");
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=text", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout("with a zebra\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_without_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files", "without-match", "-c", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout("0\n");
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=without-match", "-L", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout(format!("{BINARY}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_line_number() -> Result<()> {
+    run(
+        &["--mmap", "-n", "the", BUSTLE],
+        "tests/expected/bustle.txt.the.lowercase.line_number",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_multiple_files() -> Result<()> {
+    run(
+        &["--mmap", "-bni", "the", BUSTLE, EMPTY, FOX],
+        "tests/expected/all.the.lowercase.insensitive.line_number.byte_offset",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_invert_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "-vc", "the", BUSTLE, EMPTY])
+        .assert()
+        .success()
+        .stdout(format!("{BUSTLE}:8\n{EMPTY}:0\n"));
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "-m", "1", "-nx", "", BUSTLE])
+        .assert()
+        .success()
+        .stdout("5:\n");
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "zebra", BINARY])
+        .assert()
+        .success()
+        .stdout(format!("{BINARY}: binary file matches\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "fox"])
+        .write_stdin("a fox\na dog\n")
+        .assert()
+        .success()
+        .stdout("a fox\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always() -> Result<()> {
+    let expected = "\x1b[35m\x1b[Ktests/inputs/fox.txt\x1b[m\x1b[K\
+        \x1b[36m\x1b[K:\x1b[m\x1b[K\
+        \x1b[32m\x1b[K1\x1b[m\x1b[K\
+        \x1b[36m\x1b[K:\x1b[m\x1b[K\
+        The quick brown \x1b[01;31m\x1b[Kfox\x1b[m\x1b[K \
+        jumps over the lazy dog.\n";
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", "-n", "fox", EMPTY, FOX])
+        .env_remove("GREP_COLORS")
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_grep_colors() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", "-i", "THE", FOX])
+        .env("GREP_COLORS", "ms=04:ne")
+        .assert()
+        .success()
+        .stdout(
+            "\x1b[04mThe\x1b[m quick brown fox jumps over \
+            \x1b[04mthe\x1b[m lazy dog.\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_line_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-x", "--color=always", "And putting love away", BUSTLE])
+        .env_remove("GREP_COLORS")
+        .assert()
+        .success()
+        .stdout("\x1b[01;31m\x1b[KAnd putting love away\x1b[m\x1b[K\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_bare_is_auto() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color", "fox", FOX])
+        .assert()
+        .success()
+        .stdout("The quick brown fox jumps over the lazy dog.\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_never() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color=never", "-n", "fox", FOX])
+        .assert()
+        .success()
+        .stdout("1:The quick brown fox jumps over the lazy dog.\n");
+    Command::cargo_bin(PRG)?
+        .args(["-n", "fox", FOX])
+        .assert()
+        .success()
+        .stdout("1:The quick brown fox jumps over the lazy dog.\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn warns_dir_not_recursive() -> Result<()> {
@@ -236,11 +719,31 @@ fn warns_dir_not_recursive() -> Result<()> {
     Command::cargo_bin(PRG)?
         .args(["fox", INPUTS_DIR, FOX])
         .assert()
+        .failure()
         .stderr(predicate::str::contains("tests/inputs is a directory"))
         .stdout(predicate::str::contains(stdout));
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn no_messages() -> Result<()> {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-s", "fox", &bad, INPUTS_DIR, FOX])
+        .assert()
+        .failure()
+        .stderr("")
+        .stdout(format!(
+            "{FOX}:The quick brown fox jumps over the lazy dog.\n"
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["--no-messages", "fox", FOX])
+        .assert()
+        .success();
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn stdin() -> Result<()> {
@@ -260,6 +763,21 @@ fn stdin() -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn stdin_and_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["fox", "-", FOX])
+        .write_stdin("a piped fox\n")
+        .assert()
+        .success()
+        .stdout(format!(
+            "-:a piped fox\n{FOX}:The quick brown fox \
+             jumps over the lazy dog.\n"
+        ));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn stdin_insensitive_count() -> Result<()> {
@@ -13,6 +13,8 @@ for FILE in $INPUTS/*.txt; do
     head -c 1 $FILE > ${OUT_DIR}/${BASENAME}.c1.out
     head -c 2 $FILE > ${OUT_DIR}/${BASENAME}.c2.out
     head -c 4 $FILE > ${OUT_DIR}/${BASENAME}.c4.out
+    head -n -2 $FILE > ${OUT_DIR}/${BASENAME}.n-2.out
+    head -c -4 $FILE > ${OUT_DIR}/${BASENAME}.c-4.out
 done
 
 ALL="$INPUTS/empty.txt $INPUTS/one.txt $INPUTS/two.txt $INPUTS/three.txt \
@@ -23,3 +25,17 @@ head -n 4 $ALL > $OUT_DIR/all.n4.out
 head -c 1 $ALL > $OUT_DIR/all.c1.out
 head -c 2 $ALL > $OUT_DIR/all.c2.out
 head -c 4 $ALL > $OUT_DIR/all.c4.out
+head -n -2 $ALL > $OUT_DIR/all.n-2.out
+head -c -4 $ALL > $OUT_DIR/all.c-4.out
+
+ZERO="$INPUTS/zero.bin"
+head -z -n 2 $ZERO > $OUT_DIR/zero.bin.z.n2.out
+head -z -n -1 $ZERO > $OUT_DIR/zero.bin.z.n-1.out
+
+UTF8="$INPUTS/utf8.txt"
+head -c 16 $UTF8 > $OUT_DIR/utf8.txt.c16.out
+
+TWELVE="$INPUTS/twelve.txt"
+sed -n '3,5p' $TWELVE > $OUT_DIR/twelve.txt.from3_to5.out
+sed -n '11,$p' $TWELVE > $OUT_DIR/twelve.txt.from11.out
+head -v -n 2 $TWELVE > $OUT_DIR/twelve.txt.v.n2.out
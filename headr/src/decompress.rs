@@ -0,0 +1,23 @@
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::io::{self, BufRead, BufReader};
+use xz2::read::XzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Wrap `file` in a decoder chosen by its magic bytes. Input in any other
+/// format is returned unchanged. Concatenated streams are decoded in full.
+pub fn decoder(mut file: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    let magic = file.fill_buf()?;
+    Ok(if magic.starts_with(GZIP_MAGIC) {
+        Box::new(BufReader::new(MultiGzDecoder::new(file)))
+    } else if magic.starts_with(BZIP2_MAGIC) {
+        Box::new(BufReader::new(MultiBzDecoder::new(file)))
+    } else if magic.starts_with(XZ_MAGIC) {
+        Box::new(BufReader::new(XzDecoder::new_multi_decoder(file)))
+    } else {
+        file
+    })
+}
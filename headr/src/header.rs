@@ -0,0 +1,131 @@
+use std::{fs, path::Path, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Literal(String),
+    Path,
+    Name,
+    Size,
+}
+
+/// Parsed `--header-format`, e.g. `## {path} ({size} bytes)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderFormat {
+    fields: Vec<Field>,
+}
+
+impl FromStr for HeaderFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = vec![];
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('{') if name.is_empty() => {
+                                literal.push('{');
+                                break;
+                            }
+                            Some('}') => {
+                                let field = match name.as_str() {
+                                    "path" => Field::Path,
+                                    "name" => Field::Name,
+                                    "size" => Field::Size,
+                                    _ => return Err(format!("unknown placeholder '{{{name}}}'")),
+                                };
+                                if !literal.is_empty() {
+                                    fields.push(Field::Literal(std::mem::take(&mut literal)));
+                                }
+                                fields.push(field);
+                                break;
+                            }
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed placeholder '{{{name}'")),
+                        }
+                    }
+                }
+                '}' => match chars.next() {
+                    Some('}') => literal.push('}'),
+                    _ => return Err("unmatched '}', write '}}' for a literal brace".to_string()),
+                },
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            fields.push(Field::Literal(literal));
+        }
+        Ok(Self { fields })
+    }
+}
+
+impl HeaderFormat {
+    /// Render the header for `path`. The size is that of the file on disk, or
+    /// `?` when it has none, as for stdin.
+    pub fn render(&self, path: &str) -> String {
+        let mut out = String::new();
+        for field in &self.fields {
+            match field {
+                Field::Literal(s) => out.push_str(s),
+                Field::Path => out.push_str(path),
+                Field::Name => out.push_str(
+                    &Path::new(path)
+                        .file_name()
+                        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().into()),
+                ),
+                Field::Size => match fs::metadata(path) {
+                    Ok(metadata) if path != "-" && metadata.is_file() => {
+                        out.push_str(&metadata.len().to_string())
+                    }
+                    _ => out.push('?'),
+                },
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let format: HeaderFormat = "## {path} ({size} bytes)".parse().unwrap();
+        assert_eq!(
+            format.fields,
+            vec![
+                Field::Literal("## ".to_string()),
+                Field::Path,
+                Field::Literal(" (".to_string()),
+                Field::Size,
+                Field::Literal(" bytes)".to_string()),
+            ]
+        );
+
+        let format: HeaderFormat = "{{{name}}}".parse().unwrap();
+        assert_eq!(
+            format.fields,
+            vec![
+                Field::Literal("{".to_string()),
+                Field::Name,
+                Field::Literal("}".to_string()),
+            ]
+        );
+
+        assert!("{nope}".parse::<HeaderFormat>().is_err());
+        assert!("{path".parse::<HeaderFormat>().is_err());
+        assert!("path}".parse::<HeaderFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let format: HeaderFormat = "{name}: {size}".parse().unwrap();
+        assert_eq!(format.render("tests/inputs/twelve.txt"), "twelve.txt: 63");
+        assert_eq!(format.render("-"), "-: ?");
+    }
+}
@@ -1,141 +1,505 @@
-use clap::{App, Arg};
+mod decompress;
+mod header;
+
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use header::HeaderFormat;
 use std::{
-    error::Error,
-    fs::File,
-    io::{self, BufRead, BufReader, Read},
-    usize,
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    num::NonZeroUsize,
 };
+use unicode_segmentation::UnicodeSegmentation;
 
-type MyResult<T> = Result<T, Box<dyn Error>>;
-
-#[derive(Debug)]
+#[derive(Parser, Debug)]
+#[command(
+    name = "headr",
+    version = "0.1.0",
+    author = "circulene",
+    about = "Rust head"
+)]
 pub struct Config {
+    /// Input file(s)
+    #[arg(value_name = "FILE", default_value = "-")]
     files: Vec<String>,
-    lines: usize,
-    bytes: Option<usize>,
+
+    /// Number of lines; with a leading '-', all but the last LINES
+    #[arg(
+        short = 'n',
+        long = "lines",
+        value_name = "LINES",
+        default_value = "10",
+        allow_hyphen_values = true,
+        value_parser = parse_count
+    )]
+    lines: Count,
+
+    /// Number of bytes; with a leading '-', all but the last BYTES
+    #[arg(
+        short = 'c',
+        long = "bytes",
+        value_name = "BYTES",
+        allow_hyphen_values = true,
+        conflicts_with = "lines",
+        value_parser = parse_count
+    )]
+    bytes: Option<Count>,
+
+    /// Number of characters, never splitting a UTF-8 sequence
+    #[arg(
+        short = 'm',
+        long = "chars",
+        value_name = "CHARS",
+        conflicts_with_all = ["lines", "bytes"]
+    )]
+    chars: Option<NonZeroUsize>,
+
+    /// Number of extended grapheme clusters, never splitting emoji or combining sequences
+    #[arg(
+        long = "graphemes",
+        value_name = "GRAPHEMES",
+        conflicts_with_all = ["lines", "bytes", "chars"]
+    )]
+    graphemes: Option<NonZeroUsize>,
+
+    /// Start at line N; with --lines, print that many lines from there
+    #[arg(
+        long = "from",
+        value_name = "N",
+        conflicts_with_all = ["bytes", "chars", "graphemes"]
+    )]
+    from: Option<NonZeroUsize>,
+
+    /// Stop after line M
+    #[arg(
+        long = "to",
+        value_name = "M",
+        conflicts_with_all = ["lines", "bytes", "chars", "graphemes"]
+    )]
+    to: Option<NonZeroUsize>,
+
+    /// With --bytes, stop before a UTF-8 sequence that would be cut short
+    #[arg(long = "no-split-multibyte", requires = "bytes")]
+    no_split_multibyte: bool,
+
+    /// Line delimiter is NUL, not newline
+    #[arg(short = 'z', long = "zero-terminated")]
+    zero_terminated: bool,
+
+    /// Always print headers, even for a single file
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+
+    /// Print each header as FMT, with {path}, {name} and {size} replaced; implies --verbose
+    #[arg(long = "header-format", value_name = "FMT")]
+    header_format: Option<HeaderFormat>,
+
+    /// Don't report files that cannot be read; the exit status still shows them
+    #[arg(long = "silent-errors")]
+    silent_errors: bool,
+
+    /// Read NUL-separated input file names from FILE, or stdin when FILE is -
+    #[arg(long = "files0-from", value_name = "FILE", conflicts_with = "files")]
+    files0_from: Option<String>,
+
+    /// Decompress gzip, bzip2 and xz input, detected by its contents
+    #[arg(long = "decompress")]
+    decompress: bool,
+}
+
+/// How much of each file to print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Count {
+    /// The first N lines or bytes
+    First(usize),
+    /// Everything except the last N lines or bytes
+    AllButLast(usize),
 }
 
-fn parse_positive_int(val: &str) -> MyResult<usize> {
-    match val.parse() {
-        Ok(n) if n > 0 => Ok(n),
-        _ => Err(From::from(val)),
+/// Parse `N` or `+N` as the first N and `-N` as all but the last N.
+fn parse_count(val: &str) -> Result<Count> {
+    let (count, digits): (fn(usize) -> Count, &str) = match val.strip_prefix('-') {
+        Some(digits) => (Count::AllButLast, digits),
+        None => (Count::First, val.strip_prefix('+').unwrap_or(val)),
+    };
+    if digits.starts_with('+') {
+        bail!("invalid digit found in string");
+    }
+    match digits.parse()? {
+        0 => bail!("must be greater than zero"),
+        n => Ok(count(n)),
     }
 }
 
 #[test]
-fn test_parse_positive_int() {
-    let res = parse_positive_int("3");
+fn test_parse_count() {
+    let res = parse_count("3");
     assert!(res.is_ok());
-    assert_eq!(res.unwrap(), 3);
+    assert_eq!(res.unwrap(), Count::First(3));
 
-    let res = parse_positive_int("foo");
+    let res = parse_count("+3");
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), Count::First(3));
+
+    let res = parse_count("-3");
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), Count::AllButLast(3));
+
+    let res = parse_count("foo");
+    assert!(res.is_err());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "invalid digit found in string".to_string()
+    );
+
+    let res = parse_count("0");
     assert!(res.is_err());
-    assert_eq!(res.unwrap_err().to_string(), "foo".to_string());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "must be greater than zero".to_string()
+    );
 
-    let res = parse_positive_int("0");
+    let res = parse_count("-0");
     assert!(res.is_err());
-    assert_eq!(res.unwrap_err().to_string(), "0".to_string());
-}
-
-pub fn get_args() -> MyResult<Config> {
-    let matches = App::new("headr")
-        .version("0.1.0")
-        .author("circulene")
-        .about("Rust head")
-        .arg(
-            Arg::with_name("files")
-                .value_name("FILE")
-                .help("Input file(s)")
-                .multiple(true)
-                .default_value("-"),
-        )
-        .arg(
-            Arg::with_name("lines")
-                .short("n")
-                .long("lines")
-                .help("Number of lines")
-                .value_name("LINES")
-                .takes_value(true)
-                .default_value("10"),
-        )
-        .arg(
-            Arg::with_name("bytes")
-                .short("c")
-                .long("bytes")
-                .help("Number of bytes")
-                .value_name("BYTES")
-                .takes_value(true)
-                .conflicts_with("lines"),
-        )
-        .get_matches();
-
-    let files = matches.values_of_lossy("files").unwrap();
-    let lines = matches
-        .value_of("lines")
-        .map(parse_positive_int)
-        .transpose()
-        .map_err(|e| {
-            format!(
-                "error: invalid value '{}' for '--lines <LINES>': invalid digit found in string",
-                e
-            )
-        })?
-        .unwrap();
-    let bytes = matches
-        .value_of("bytes")
-        .map(parse_positive_int)
-        .transpose()
-        .map_err(|e| {
-            format!(
-                "error: invalid value '{}' for '--bytes <BYTES>': invalid digit found in string",
-                e
-            )
-        })?;
-
-    Ok(Config {
-        files,
-        lines,
-        bytes,
-    })
-}
-
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
-    }
-}
-
-pub fn run(config: Config) -> MyResult<()> {
-    for (i, filename) in config.files.iter().enumerate() {
-        match open(filename) {
-            Err(err) => eprintln!("{}: {}", filename, err),
-            Ok(mut file) => {
-                // print file header
-                if config.files.len() > 1 {
-                    let spacer = if i > 0 { "\n" } else { "" };
-                    println!("{}==> {} <==", spacer, filename);
-                }
-
-                if let Some(bytes) = config.bytes {
-                    let mut handle = file.take(bytes as u64);
-                    let mut buf = vec![0; bytes];
-                    let size = handle.read(&mut buf)?;
-                    let str = String::from_utf8_lossy(&buf[..size]);
-                    print!("{}", str);
-                } else {
-                    let mut line = String::new();
-                    for _ in 0..config.lines {
-                        let size = file.read_line(&mut line)?;
-                        if size == 0 {
-                            break;
-                        }
-                        print!("{}", line);
-                        line.clear();
-                    }
-                }
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "must be greater than zero".to_string()
+    );
+
+    let res = parse_count("--3");
+    assert!(res.is_err());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "invalid digit found in string".to_string()
+    );
+
+    let res = parse_count("-+3");
+    assert!(res.is_err());
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "invalid digit found in string".to_string()
+    );
+}
+
+pub fn get_args() -> Result<Config> {
+    let config = Config::try_parse()?;
+    Ok(config)
+}
+
+fn open(filename: &str, decompress: bool) -> Result<Box<dyn BufRead>> {
+    let file: Box<dyn BufRead> = match filename {
+        "-" => Box::new(BufReader::new(io::stdin())),
+        _ => Box::new(BufReader::new(File::open(filename)?)),
+    };
+    if decompress {
+        Ok(decompress::decoder(file)?)
+    } else {
+        Ok(file)
+    }
+}
+
+/// Print the first `n` lines ending in `delimiter`.
+fn print_first_lines(
+    mut file: impl BufRead,
+    out: &mut impl Write,
+    n: usize,
+    delimiter: u8,
+) -> Result<()> {
+    let mut line = vec![];
+    for _ in 0..n {
+        if file.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        out.write_all(&line)?;
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Whether `byte` continues a multibyte UTF-8 sequence rather than starting a character.
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+/// Print the first `n` bytes, streaming through a small buffer so memory use
+/// does not depend on `n`. With `no_split`, a character that would be cut
+/// short at the limit is left out entirely: the last few bytes are held back
+/// until the byte after the limit shows whether they complete a character.
+fn print_first_bytes(
+    mut file: impl BufRead,
+    out: &mut impl Write,
+    n: usize,
+    no_split: bool,
+) -> Result<()> {
+    if !no_split {
+        io::copy(&mut file.take(n as u64), out)?;
+        return Ok(());
+    }
+    // hold back the last 3 bytes: the start of a character cut short by the
+    // limit is among them
+    let keep = 3;
+    let mut held = vec![];
+    let mut remaining = n;
+    while remaining > 0 {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let len = buf.len().min(remaining);
+        held.extend_from_slice(&buf[..len]);
+        file.consume(len);
+        remaining -= len;
+        if held.len() > keep {
+            let ready = held.len() - keep;
+            out.write_all(&held[..ready])?;
+            held.drain(..ready);
+        }
+    }
+    if remaining == 0
+        && file
+            .fill_buf()?
+            .first()
+            .is_some_and(|&b| is_continuation(b))
+    {
+        if let Some(start) = held.iter().rposition(|&b| !is_continuation(b)) {
+            held.truncate(start);
+        }
+    }
+    out.write_all(&held)?;
+    Ok(())
+}
+
+/// Print the first `n` characters. Continuation bytes stay with the character
+/// they follow, so a UTF-8 sequence is never split.
+fn print_first_chars(mut file: impl BufRead, out: &mut impl Write, n: usize) -> Result<()> {
+    let mut chars = 0;
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let end = buf.iter().position(|&b| {
+            if !is_continuation(b) {
+                chars += 1;
+            }
+            chars > n
+        });
+        if let Some(end) = end {
+            out.write_all(&buf[..end])?;
+            break;
+        }
+        out.write_all(buf)?;
+        let len = buf.len();
+        file.consume(len);
+    }
+    Ok(())
+}
+
+/// Print the first `n` extended grapheme clusters. No cluster continues past a
+/// line feed, so each line is segmented on its own; invalid UTF-8 is replaced.
+fn print_first_graphemes(mut file: impl BufRead, out: &mut impl Write, n: usize) -> Result<()> {
+    let mut remaining = n;
+    let mut line = vec![];
+    while remaining > 0 {
+        line.clear();
+        if file.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&line);
+        match text.grapheme_indices(true).nth(remaining) {
+            Some((end, _)) => {
+                out.write_all(text[..end].as_bytes())?;
+                break;
+            }
+            None => {
+                out.write_all(text.as_bytes())?;
+                remaining -= text.graphemes(true).count();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print all but the last `n` lines, holding back a window of `n` lines until
+/// a later line shows they are not among the last.
+fn print_all_but_last_lines(
+    mut file: impl BufRead,
+    out: &mut impl Write,
+    n: usize,
+    delimiter: u8,
+) -> Result<()> {
+    let mut window = VecDeque::with_capacity(n + 1);
+    loop {
+        let mut line = vec![];
+        if file.read_until(delimiter, &mut line)? == 0 {
+            break;
+        }
+        window.push_back(line);
+        if window.len() > n {
+            out.write_all(&window.pop_front().unwrap())?;
+        }
+    }
+    Ok(())
+}
+
+/// Print all but the last `n` bytes, holding back the most recent `n` bytes read.
+fn print_all_but_last_bytes(mut file: impl BufRead, out: &mut impl Write, n: usize) -> Result<()> {
+    let mut pending = vec![];
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        pending.extend_from_slice(buf);
+        let len = buf.len();
+        file.consume(len);
+        if pending.len() > n {
+            let ready = pending.len() - n;
+            out.write_all(&pending[..ready])?;
+            pending.drain(..ready);
+        }
+    }
+    Ok(())
+}
+
+/// Skip `n` lines ending in `delimiter` without copying them out of the buffer.
+fn skip_lines(file: &mut impl BufRead, n: usize, delimiter: u8) -> Result<()> {
+    let mut skipped = 0;
+    while skipped < n {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        match buf.iter().position(|&b| b == delimiter) {
+            Some(i) => {
+                file.consume(i + 1);
+                skipped += 1;
+            }
+            None => {
+                let len = buf.len();
+                file.consume(len);
             }
         }
     }
     Ok(())
 }
+
+/// Read the NUL-separated file names in `filename`; a final NUL is optional.
+fn read_files0(filename: &str) -> Result<Vec<String>> {
+    let mut list = vec![];
+    open(filename, false)
+        .and_then(|mut file| Ok(file.read_to_end(&mut list)?))
+        .map_err(|e| anyhow!("{filename}: {e}"))?;
+    if list.last() == Some(&b'\0') {
+        list.pop();
+    }
+    if list.is_empty() {
+        return Ok(vec![]);
+    }
+    list.split(|&b| b == b'\0')
+        .map(|name| match name {
+            b"" => Err(anyhow!("{filename}: invalid zero-length file name")),
+            name => Ok(String::from_utf8_lossy(name).into_owned()),
+        })
+        .collect()
+}
+
+/// Size of `filename` when it is a regular file read as is.
+fn regular_file_size(filename: &str, decompress: bool) -> Option<usize> {
+    if filename == "-" || decompress {
+        return None;
+    }
+    fs::metadata(filename)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .and_then(|metadata| usize::try_from(metadata.len()).ok())
+}
+
+#[test]
+fn test_regular_file_size() {
+    assert_eq!(
+        regular_file_size("tests/inputs/twelve.txt", false),
+        Some(63)
+    );
+    assert_eq!(regular_file_size("tests/inputs/twelve.txt", true), None);
+    assert_eq!(regular_file_size("tests/inputs", false), None);
+    assert_eq!(regular_file_size("-", false), None);
+}
+
+/// Print the head of one file as selected by `config`. When the size of the
+/// file is known, byte counts are clamped to it, and all but the last N bytes
+/// are read without holding any back.
+fn head(
+    config: &Config,
+    mut file: impl BufRead,
+    size: Option<usize>,
+    out: &mut impl Write,
+) -> Result<()> {
+    if let Some(chars) = config.chars {
+        return print_first_chars(file, out, chars.get());
+    }
+    if let Some(graphemes) = config.graphemes {
+        return print_first_graphemes(file, out, graphemes.get());
+    }
+    if let Some(bytes) = config.bytes {
+        let no_split = config.no_split_multibyte;
+        return match (bytes, size) {
+            (Count::First(n), Some(size)) => print_first_bytes(file, out, n.min(size), no_split),
+            (Count::First(n), None) => print_first_bytes(file, out, n, no_split),
+            (Count::AllButLast(n), Some(size)) => {
+                print_first_bytes(file, out, size.saturating_sub(n), false)
+            }
+            (Count::AllButLast(n), None) => print_all_but_last_bytes(file, out, n),
+        };
+    }
+
+    let delimiter = if config.zero_terminated { b'\0' } else { b'\n' };
+    let from = config.from.map_or(1, NonZeroUsize::get);
+    skip_lines(&mut file, from - 1, delimiter)?;
+    let lines = match config.to {
+        Some(to) => Count::First(to.get() + 1 - from),
+        None => config.lines,
+    };
+    match lines {
+        Count::First(lines) => print_first_lines(file, out, lines, delimiter),
+        Count::AllButLast(lines) => print_all_but_last_lines(file, out, lines, delimiter),
+    }
+}
+
+/// Print the head of every file in `config` to `out`. Files that cannot be
+/// read are reported on stderr, unless silenced, and skipped; returns how
+/// many there were.
+pub fn run(config: Config, mut out: impl Write) -> Result<usize> {
+    if let (Some(from), Some(to)) = (config.from, config.to) {
+        if to < from {
+            bail!("--to {to} is before --from {from}");
+        }
+    }
+    let files = match &config.files0_from {
+        Some(list) => read_files0(list)?,
+        None => config.files.clone(),
+    };
+    let mut num_errors = 0;
+    for (i, filename) in files.iter().enumerate() {
+        let result = open(filename, config.decompress).and_then(|file| {
+            // print file header
+            if let Some(format) = &config.header_format {
+                writeln!(out, "{}", format.render(filename))?;
+            } else if config.verbose || files.len() > 1 {
+                let spacer = if i > 0 { "\n" } else { "" };
+                writeln!(out, "{}==> {} <==", spacer, filename)?;
+            }
+            let size = regular_file_size(filename, config.decompress);
+            head(&config, file, size, &mut out)
+        });
+        if let Err(err) = result {
+            num_errors += 1;
+            if !config.silent_errors {
+                eprintln!("{}: {}", filename, err);
+            }
+        }
+    }
+    out.flush()?;
+    Ok(num_errors)
+}
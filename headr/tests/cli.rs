@@ -1,5 +1,6 @@
 use anyhow::Result;
 use assert_cmd::Command;
+use clap::Parser;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
@@ -12,6 +13,9 @@ const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TWELVE: &str = "./tests/inputs/twelve.txt";
+const ZERO: &str = "./tests/inputs/zero.bin";
+const UTF8: &str = "./tests/inputs/utf8.txt";
+const GRAPHEMES: &str = "./tests/inputs/graphemes.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -70,7 +74,7 @@ fn dies_bad_lines() -> Result<()> {
 // --------------------------------------------------
 #[test]
 fn dies_bytes_and_lines() -> Result<()> {
-    let msg = "The argument '--lines <LINES>' cannot be \
+    let msg = "the argument '--lines <LINES>' cannot be \
                used with '--bytes <BYTES>'";
 
     Command::cargo_bin(PRG)?
@@ -90,11 +94,41 @@ fn skips_bad_file() -> Result<()> {
     Command::cargo_bin(PRG)?
         .args([EMPTY, &bad, ONE])
         .assert()
+        .failure()
         .stderr(predicate::str::is_match(expected)?);
 
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn silent_errors() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = fs::read_to_string("tests/expected/one.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--silent-errors", &bad, ONE])
+        .assert()
+        .failure()
+        .stderr("")
+        .stdout(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_directory() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/one.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("tests/inputs: "))
+        .stdout(predicate::str::contains(expected));
+
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
     // Extra work here due to lossy UTF
@@ -419,3 +453,304 @@ fn multiple_files_c4() -> Result<()> {
         "tests/expected/all.c4.out",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn three_n_minus2() -> Result<()> {
+    run(&[THREE, "-n", "-2"], "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn twelve_n_minus2() -> Result<()> {
+    run(&[TWELVE, "-n", "-2"], "tests/expected/twelve.txt.n-2.out")
+}
+
+#[test]
+fn twelve_c_minus4() -> Result<()> {
+    run(&[TWELVE, "-c", "-4"], "tests/expected/twelve.txt.c-4.out")
+}
+
+#[test]
+fn twelve_n_minus2_stdin() -> Result<()> {
+    run_stdin(&["-n", "-2"], TWELVE, "tests/expected/twelve.txt.n-2.out")
+}
+
+#[test]
+fn twelve_c_minus4_stdin() -> Result<()> {
+    run_stdin(&["-c", "-4"], TWELVE, "tests/expected/twelve.txt.c-4.out")
+}
+
+#[test]
+fn multiple_files_n_minus2() -> Result<()> {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TWELVE, "-n", "-2"],
+        "tests/expected/all.n-2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus4() -> Result<()> {
+    run(
+        &["-c", "-4", EMPTY, ONE, TWO, THREE, TWELVE],
+        "tests/expected/all.c-4.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_terminated_n2() -> Result<()> {
+    run(&["-z", "-n", "2", ZERO], "tests/expected/zero.bin.z.n2.out")
+}
+
+#[test]
+fn zero_terminated_n_minus1() -> Result<()> {
+    run(
+        &["--zero-terminated", "-n", "-1", ZERO],
+        "tests/expected/zero.bin.z.n-1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn library_run_writes_to_output() -> Result<()> {
+    let config = headr::Config::try_parse_from([PRG, "-n", "2", TWELVE])?;
+    let mut output = vec![];
+    assert_eq!(headr::run(config, &mut output)?, 0);
+    let expected = fs::read_to_string("tests/expected/twelve.txt.n2.out")?;
+    assert_eq!(String::from_utf8(output)?, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn decompress_gzip() -> Result<()> {
+    run(
+        &["--decompress", "-n", "2", "tests/inputs/twelve.txt.gz"],
+        "tests/expected/twelve.txt.n2.out",
+    )
+}
+
+#[test]
+fn decompress_bzip2() -> Result<()> {
+    run(
+        &["--decompress", "-n", "2", "tests/inputs/twelve.txt.bz2"],
+        "tests/expected/twelve.txt.n2.out",
+    )
+}
+
+#[test]
+fn decompress_xz() -> Result<()> {
+    run(
+        &["--decompress", "-n", "2", "tests/inputs/twelve.txt.xz"],
+        "tests/expected/twelve.txt.n2.out",
+    )
+}
+
+#[test]
+fn decompress_stdin() -> Result<()> {
+    let input = fs::read("tests/inputs/twelve.txt.gz")?;
+    let expected = fs::read_to_string("tests/expected/twelve.txt.c4.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--decompress", "-c", "4"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+#[test]
+fn decompress_uncompressed() -> Result<()> {
+    run(
+        &["--decompress", "-n", "2", TWELVE],
+        "tests/expected/twelve.txt.n2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8_chars() -> Result<()> {
+    run(&["-m", "13", UTF8], "tests/expected/utf8.txt.m13.out")
+}
+
+#[test]
+fn utf8_bytes_raw() -> Result<()> {
+    let expected = fs::read("tests/expected/utf8.txt.c16.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-c", "16", UTF8])
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+#[test]
+fn utf8_bytes_no_split() -> Result<()> {
+    run(
+        &["-c", "16", "--no-split-multibyte", UTF8],
+        "tests/expected/utf8.txt.c16.no_split.out",
+    )
+}
+
+#[test]
+fn dies_chars_and_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "1", "-c", "2", UTF8])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_larger_than_file() -> Result<()> {
+    run(&["-c", "2000000000", TWELVE], TWELVE)
+}
+
+#[test]
+fn bytes_larger_than_file_stdin() -> Result<()> {
+    run_stdin(&["-c", "2000000000"], TWELVE, TWELVE)
+}
+
+// --------------------------------------------------
+#[test]
+fn twelve_from_to() -> Result<()> {
+    run(
+        &["--from", "3", "--to", "5", TWELVE],
+        "tests/expected/twelve.txt.from3_to5.out",
+    )
+}
+
+#[test]
+fn twelve_from() -> Result<()> {
+    run(
+        &["--from", "11", TWELVE],
+        "tests/expected/twelve.txt.from11.out",
+    )
+}
+
+#[test]
+fn twelve_from_n2_stdin() -> Result<()> {
+    run_stdin(
+        &["--from", "3", "-n", "3"],
+        TWELVE,
+        "tests/expected/twelve.txt.from3_to5.out",
+    )
+}
+
+#[test]
+fn twelve_to() -> Result<()> {
+    run(&["--to", "2", TWELVE], "tests/expected/twelve.txt.n2.out")
+}
+
+#[test]
+fn dies_to_before_from() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--from", "5", "--to", "3", TWELVE])
+        .assert()
+        .failure()
+        .stderr("--to 3 is before --from 5\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> Result<()> {
+    run(
+        &["--files0-from", "tests/inputs/all.files0", "-n", "2"],
+        "tests/expected/all.n2.out",
+    )
+}
+
+#[test]
+fn files0_from_stdin() -> Result<()> {
+    run_stdin(
+        &["--files0-from", "-", "-n", "2"],
+        "tests/inputs/all.files0",
+        "tests/expected/all.n2.out",
+    )
+}
+
+#[test]
+fn dies_files0_from_with_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", "tests/inputs/all.files0", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn graphemes_within_line() -> Result<()> {
+    run(
+        &["--graphemes", "4", GRAPHEMES],
+        "tests/expected/graphemes.txt.g4.out",
+    )
+}
+
+#[test]
+fn graphemes_across_lines_stdin() -> Result<()> {
+    run_stdin(
+        &["--graphemes", "6"],
+        GRAPHEMES,
+        "tests/expected/graphemes.txt.g6.out",
+    )
+}
+
+#[test]
+fn dies_graphemes_and_chars() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--graphemes", "1", "-m", "2", GRAPHEMES])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn verbose_single_file() -> Result<()> {
+    run(
+        &["-v", "-n", "2", TWELVE],
+        "tests/expected/twelve.txt.v.n2.out",
+    )
+}
+
+#[test]
+fn header_format() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([
+            "--header-format",
+            "## {name} ({size} bytes)",
+            "-n",
+            "1",
+            TWO,
+            TWELVE,
+        ])
+        .assert()
+        .success()
+        .stdout("## two.txt (23 bytes)\nTwo lines.\n## twelve.txt (63 bytes)\none\n");
+
+    Ok(())
+}
+
+#[test]
+fn dies_bad_header_format() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--header-format", "{size", TWELVE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unclosed placeholder"));
+
+    Ok(())
+}
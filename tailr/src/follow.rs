@@ -0,0 +1,282 @@
+use crate::{expand_glob, output::Output, watch::Watcher};
+use anyhow::Result;
+use clap::ValueEnum;
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::fs::MetadataExt,
+    thread,
+    time::Duration,
+};
+
+/// What to keep following when a file is renamed or replaced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FollowMode {
+    /// The open file, wherever it moves
+    Descriptor,
+    /// Whatever file has the name, reopened after rotation
+    Name,
+}
+
+/// A followed file and how much of it has been printed.
+struct Watched {
+    name: String,
+    file: Option<File>,
+    /// Device and inode of the open file
+    id: (u64, u64),
+    pos: u64,
+    /// Checks in a row that found the size unchanged
+    unchanged: u64,
+}
+
+pub struct Follower {
+    files: Vec<Watched>,
+    mode: FollowMode,
+    retry: bool,
+    interval: Duration,
+    max_unchanged: u64,
+    pid: Option<i32>,
+    watcher: Option<Watcher>,
+    headers: bool,
+    /// Glob patterns for files to follow once they appear
+    patterns: Vec<String>,
+    /// The file whose data was printed last
+    last: Option<String>,
+}
+
+impl Follower {
+    pub fn new(mode: FollowMode, retry: bool, interval: Duration) -> Self {
+        Self {
+            files: vec![],
+            mode,
+            retry,
+            interval,
+            max_unchanged: 5,
+            pid: None,
+            watcher: None,
+            headers: false,
+            patterns: vec![],
+            last: None,
+        }
+    }
+
+    /// Wait for change notifications (inotify, kqueue) where the platform
+    /// has them, rather than checking every interval.
+    pub fn use_inotify(&mut self) {
+        self.watcher = Watcher::new();
+    }
+
+    /// When polling by name, only check the name after the open file has
+    /// been found unchanged `n` times in a row.
+    pub fn max_unchanged_stats(&mut self, n: u64) {
+        self.max_unchanged = n;
+    }
+
+    /// Stop after process `pid` dies, once what it wrote has been printed.
+    pub fn until_exit(&mut self, pid: i32) {
+        self.pid = Some(pid);
+    }
+
+    /// Print a header naming the file whenever output switches to another
+    /// file.
+    pub fn show_headers(&mut self) {
+        self.headers = true;
+    }
+
+    /// Note that the output so far ended with data from `name`.
+    pub fn printed(&mut self, name: &str) {
+        self.last = Some(name.to_string());
+    }
+
+    /// Follow `name`, whose first `pos` bytes have been printed already.
+    /// A file that could not be opened is only followed with `--retry`.
+    pub fn add(&mut self, name: &str, pos: Option<u64>) {
+        let file = pos.and_then(|_| File::open(name).ok());
+        if file.is_none() && !self.retry {
+            return;
+        }
+        self.files.push(Watched::new(name, file, pos.unwrap_or(0)));
+    }
+
+    /// Also follow files that come to match the glob `pattern`.
+    pub fn add_pattern(&mut self, pattern: &str) {
+        self.patterns.push(pattern.to_string());
+    }
+
+    /// Start following files that have appeared matching a pattern, from
+    /// their start.
+    fn find_new(&mut self) {
+        for pattern in &self.patterns {
+            for name in expand_glob(pattern).unwrap_or_default() {
+                if self.files.iter().any(|watched| watched.name == name) {
+                    continue;
+                }
+                if let Ok(file) = File::open(&name) {
+                    eprintln!("'{}' has appeared;  following new file", name);
+                    self.files.push(Watched::new(&name, Some(file), 0));
+                }
+            }
+        }
+    }
+
+    /// Print data as it is appended until no file is left to follow or the
+    /// process given by `--pid` is gone.
+    pub fn run(&mut self, out: &mut Output<impl Write>) -> Result<()> {
+        loop {
+            self.find_new();
+            if self.files.is_empty() && self.patterns.is_empty() {
+                eprintln!("no files remaining");
+                return Ok(());
+            }
+            let alive = self.pid.is_none_or(is_alive);
+            // Watch before reading, so no change goes unnoticed in between
+            let watched = self.watcher.as_mut().map(|watcher| {
+                self.files
+                    .iter()
+                    .filter(|file| !watcher.watch(&file.name))
+                    .count()
+                    == 0
+            });
+            let mut i = 0;
+            while i < self.files.len() {
+                if self.poll(i, out)? {
+                    i += 1;
+                } else {
+                    self.files.remove(i);
+                }
+            }
+            if !alive {
+                return Ok(());
+            }
+            match (&self.watcher, watched) {
+                (Some(watcher), Some(all)) => {
+                    // Without a watch on everything, or to check on the
+                    // process or for new files, still wake every interval
+                    let poll = !all || self.pid.is_some() || !self.patterns.is_empty();
+                    let timeout = poll.then_some(self.interval);
+                    watcher.wait(timeout)?;
+                }
+                _ => thread::sleep(self.interval),
+            }
+        }
+    }
+
+    /// Print what was appended to file `i`. Returns whether to keep
+    /// following it.
+    fn poll(&mut self, i: usize, out: &mut Output<impl Write>) -> Result<bool> {
+        let (mode, retry) = (self.mode, self.retry);
+        let watched = &mut self.files[i];
+        let last = &mut self.last;
+        // A rotation shows up as an inotify event, or else as a file that
+        // has stopped growing
+        let check_name = mode == FollowMode::Name
+            && (self.watcher.is_some() || watched.unchanged >= self.max_unchanged);
+        let reopen = check_name || watched.file.is_none();
+        if reopen {
+            // Finish the old file before a rotation replaces it
+            watched.print_appended(check_name, self.headers, last, out)?;
+            if !watched.reopen() && !retry {
+                return Ok(false);
+            }
+        }
+        watched.print_appended(check_name, self.headers, last, out)?;
+        Ok(true)
+    }
+}
+
+/// Whether process `pid` exists, even if it is not ours to signal.
+fn is_alive(pid: i32) -> bool {
+    // SAFETY: signal 0 only checks that the process could be signalled
+    let res = unsafe { libc::kill(pid, 0) };
+    res == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+impl Watched {
+    fn new(name: &str, file: Option<File>, pos: u64) -> Self {
+        let id = file
+            .as_ref()
+            .and_then(|file| file.metadata().ok())
+            .map_or((0, 0), |meta| (meta.dev(), meta.ino()));
+        Self {
+            name: name.to_string(),
+            file,
+            id,
+            pos,
+            unchanged: 0,
+        }
+    }
+
+    /// Print what was appended to the open file since it was last read.
+    /// `last` names the file whose data was printed last.
+    fn print_appended(
+        &mut self,
+        check_name: bool,
+        headers: bool,
+        last: &mut Option<String>,
+        out: &mut Output<impl Write>,
+    ) -> Result<()> {
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        let size = file.metadata()?.len();
+        if size == self.pos && !check_name {
+            self.unchanged += 1;
+        } else {
+            self.unchanged = 0;
+        }
+        if size < self.pos {
+            eprintln!("{}: file truncated", self.name);
+            self.pos = 0;
+        }
+        if size > self.pos {
+            file.seek(SeekFrom::Start(self.pos))?;
+            let mut buf = vec![];
+            self.pos += file.read_to_end(&mut buf)? as u64;
+            if headers && last.as_ref() != Some(&self.name) {
+                out.header(&self.name)?;
+                *last = Some(self.name.clone());
+            }
+            out.source(&self.name)?;
+            out.at(self.pos - buf.len() as u64)?;
+            out.write_all(&buf)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Check that the name still leads to the open file, opening the new
+    /// one after a rotation. Returns whether there is a file to read.
+    fn reopen(&mut self) -> bool {
+        let meta = match fs::metadata(&self.name) {
+            Ok(meta) => meta,
+            Err(e) => {
+                if self.file.take().is_some() {
+                    eprintln!("'{}' has become inaccessible: {}", self.name, e);
+                }
+                return false;
+            }
+        };
+        let id = (meta.dev(), meta.ino());
+        if self.file.is_some() && id == self.id {
+            return true;
+        }
+        match File::open(&self.name) {
+            Ok(file) => {
+                let what = if self.file.is_some() {
+                    "been replaced"
+                } else {
+                    "appeared"
+                };
+                eprintln!("'{}' has {};  following new file", self.name, what);
+                self.file = Some(file);
+                self.id = id;
+                self.pos = 0;
+                true
+            }
+            Err(_) => {
+                self.file = None;
+                false
+            }
+        }
+    }
+}
@@ -1,16 +1,29 @@
+mod follow;
+mod output;
+mod watch;
+
+use crate::follow::{FollowMode, Follower};
+use crate::output::Output;
 use crate::TakeValue::*;
 use anyhow::{Error, Result};
-use clap::{builder::TypedValueParser, command, Arg, Command, Parser};
+use clap::{builder::TypedValueParser, Arg, Command, Parser, ValueEnum};
+use glob::Pattern;
+use memmap2::MmapOptions;
 use once_cell::sync::OnceCell;
 use regex::Regex;
 use std::{
-    cmp::max,
-    fs::File,
-    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    collections::VecDeque,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write},
+    time::Duration,
 };
 
 static NUM_RE: OnceCell<Regex> = OnceCell::new();
 
+/// How much to read at a time when scanning a file backwards
+const BLOCK_SIZE: u64 = 64 * 1024;
+
 #[derive(PartialEq, Clone, Debug)]
 enum TakeValue {
     PlusZero,
@@ -75,13 +88,37 @@ fn parse_num(value: &str) -> Result<TakeValue> {
     }
 }
 
+/// When to highlight matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ColorWhen {
+    /// When stdout is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Input file(s)
-    #[arg(value_name = "FILE", required = true)]
+    /// Input file(s), or - for standard input (the default)
+    #[arg(value_name = "FILE")]
     files: Vec<String>,
 
+    /// Expand FILE arguments as glob patterns, e.g. 'logs/*.log', and while
+    /// following, follow files that come to match them
+    #[arg(long = "glob")]
+    glob: bool,
+
+    /// Tail the files in DIR, and while following, files created in it
+    #[arg(long = "directory", value_name = "DIR")]
+    directories: Vec<String>,
+
     /// Number of lines
     #[arg(
         short = 'n',
@@ -106,113 +143,522 @@ struct Args {
     bytes: Option<TakeValue>,
 
     /// Supress headers
-    #[arg(short = 'q', long = "quiet")]
+    #[arg(short = 'q', long = "quiet", overrides_with = "verbose")]
     quiet: bool,
+
+    /// Don't report files that cannot be read; the exit status still shows
+    /// them
+    #[arg(short = 's', long = "silent")]
+    silent: bool,
+
+    /// Always print headers
+    #[arg(short = 'v', long = "verbose", overrides_with = "quiet")]
+    verbose: bool,
+
+    /// Only print lines matching PATTERN
+    #[arg(long = "grep", value_name = "PATTERN", value_parser = parse_pattern)]
+    grep: Option<regex::bytes::Regex>,
+
+    /// Only print lines not matching the --grep pattern
+    #[arg(long = "grep-invert", requires = "grep")]
+    grep_invert: bool,
+
+    /// Highlight what matches PATTERN
+    #[arg(long = "highlight", value_name = "PATTERN", value_parser = parse_pattern)]
+    highlight: Option<regex::bytes::Regex>,
+
+    /// When to highlight: auto, always or never
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value = "auto",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    color: ColorWhen,
+
+    /// Print lines as they are (text) or as JSON objects giving the file and
+    /// offset of each line (json)
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "text"
+    )]
+    format: Format,
+
+    /// Map the end of a file into memory to print it, rather than reading it
+    /// through a buffer. A file truncated while it is printed kills tailr
+    /// with SIGBUS
+    #[arg(long = "mmap")]
+    mmap: bool,
+
+    /// Line delimiter is NUL, not newline
+    #[arg(short = 'z', long = "zero-terminated")]
+    zero_terminated: bool,
+
+    /// Output appended data as the file grows, following the open file
+    /// (descriptor) or whatever file has the name (name)
+    #[arg(
+        short = 'f',
+        long = "follow",
+        value_name = "HOW",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "descriptor",
+        value_enum
+    )]
+    follow: Option<FollowMode>,
+
+    /// Same as --follow=name --retry
+    #[arg(short = 'F')]
+    follow_name: bool,
+
+    /// With --follow=name and without inotify, reopen a file that has not
+    /// changed size after N checks, to see if it was rotated
+    #[arg(
+        long = "max-unchanged-stats",
+        value_name = "N",
+        default_value = "5",
+        value_parser(clap::value_parser!(u64).range(1..))
+    )]
+    max_unchanged_stats: u64,
+
+    /// With -f, stop after process PID dies
+    #[arg(long = "pid", value_name = "PID", value_parser(clap::value_parser!(i32).range(1..)))]
+    pid: Option<i32>,
+
+    /// Keep trying to open a file that is inaccessible
+    #[arg(long = "retry")]
+    retry: bool,
+
+    /// Check files every interval instead of waiting for inotify events
+    #[arg(long = "disable-inotify")]
+    disable_inotify: bool,
+
+    /// Seconds to sleep between checks for new data
+    #[arg(
+        long = "sleep-interval",
+        value_name = "SECS",
+        default_value = "1.0",
+        value_parser = parse_interval
+    )]
+    sleep_interval: Duration,
+}
+
+impl Args {
+    /// Whether to introduce each file's output with its name, given how many
+    /// files there are to begin with.
+    fn headers(&self, num_files: usize) -> bool {
+        // Files found by pattern may be joined by more later
+        let more = self.glob || !self.directories.is_empty();
+        self.verbose || (!self.quiet && (num_files > 1 || more))
+    }
+
+    /// Glob patterns for files to tail, which are looked for again while
+    /// following.
+    fn patterns(&self) -> Vec<String> {
+        let globs = self
+            .files
+            .iter()
+            .filter(|file| self.glob && *file != "-")
+            .cloned();
+        let dirs = self.directories.iter().map(|dir| dir_pattern(dir));
+        globs.chain(dirs).collect()
+    }
+
+    /// The byte that ends a line.
+    fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+fn parse_interval(value: &str) -> Result<Duration> {
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| Error::msg(format!("invalid number of seconds: '{}'", value)))
+}
+
+fn use_color(when: ColorWhen) -> bool {
+    match when {
+        ColorWhen::Always => true,
+        ColorWhen::Never => false,
+        ColorWhen::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|val| val.is_empty())
+        }
+    }
+}
+
+fn parse_pattern(value: &str) -> Result<regex::bytes::Regex> {
+    Ok(regex::bytes::Regex::new(value)?)
+}
+
+/// A glob pattern for the files in `dir`.
+fn dir_pattern(dir: &str) -> String {
+    format!(
+        "{}/*",
+        Pattern::escape(dir.strip_suffix('/').unwrap_or(dir))
+    )
+}
+
+/// The files to tail: the FILE arguments, expanded with `--glob`, then the
+/// files in each directory. Standard input when none are given.
+fn input_files(args: &Args) -> Result<Vec<String>> {
+    for dir in &args.directories {
+        let meta = fs::metadata(dir).map_err(|e| Error::msg(format!("{}: {}", dir, e)))?;
+        if !meta.is_dir() {
+            return Err(Error::msg(format!("{}: Not a directory", dir)));
+        }
+    }
+    if args.files.is_empty() && args.directories.is_empty() {
+        return Ok(vec!["-".to_string()]);
+    }
+    let mut files = vec![];
+    for file in &args.files {
+        if args.glob && file != "-" {
+            files.extend(expand_glob(file)?);
+        } else {
+            files.push(file.clone());
+        }
+    }
+    for dir in &args.directories {
+        files.extend(expand_glob(&dir_pattern(dir))?);
+    }
+    Ok(files)
+}
+
+/// The regular files matching the glob `pattern`, in order.
+fn expand_glob(pattern: &str) -> Result<Vec<String>> {
+    let paths = glob::glob(pattern).map_err(|e| Error::msg(format!("{}: {}", pattern, e)))?;
+    Ok(paths
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
 }
 
 fn open_file(filename: &str) -> Result<File> {
     File::open(filename).map_err(|e| Error::msg(format!("{}: {}", filename, e)))
 }
 
-fn open_bufread(filename: &str) -> Result<Box<dyn BufRead>> {
-    let file = open_file(filename)?;
-    Ok(Box::new(BufReader::new(file)))
+/// Copy bytes `start..end` of a file to `out`.
+fn print_range(
+    mut file: impl Read + Seek,
+    start: u64,
+    end: u64,
+    out: &mut Output<impl Write>,
+) -> Result<()> {
+    file.seek(SeekFrom::Start(start))?;
+    out.at(start)?;
+    io::copy(&mut file.take(end - start), out)?;
+    Ok(())
 }
 
-fn count_lines_bytes(filename: &str) -> Result<(i64, i64)> {
-    let mut lines: i64 = 0;
-    let mut buf = Vec::new();
-    let mut bytes: i64 = 0;
-    let mut file = open_bufread(filename)?;
-    loop {
-        let read_bytes = file.read_until(b'\n', &mut buf)?;
-        if read_bytes == 0 {
-            break;
+/// Find where the last `num` lines of a file of `size` bytes start by
+/// reading blocks backwards from the end, so only the tail is read.
+fn last_lines_start(
+    file: &mut (impl Read + Seek),
+    num: u64,
+    size: u64,
+    delimiter: u8,
+) -> Result<u64> {
+    let mut buf = vec![0; BLOCK_SIZE as usize];
+    let mut newlines = 0;
+    let mut end = size;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE);
+        let block = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(block)?;
+        for (i, _) in block
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &b)| b == delimiter)
+        {
+            let pos = start + i as u64;
+            // A delimiter ending the file ends the last line
+            if pos + 1 == size {
+                continue;
+            }
+            newlines += 1;
+            if newlines == num {
+                return Ok(pos + 1);
+            }
         }
-        bytes += read_bytes as i64;
-        lines += 1;
-        buf.clear();
+        end = start;
     }
-    Ok((lines, bytes))
+    Ok(0)
 }
 
-fn get_start_index(take_val: &TakeValue, total: i64) -> Option<i64> {
-    match take_val {
-        TakeNum(num) => {
-            let num = *num;
-            if num == 0 || total == 0 || num > total {
-                None
-            } else if num < 0 {
-                Some(max(total + num, 0))
+/// Print the requested end of a stream that cannot seek. Counting from the
+/// end keeps only as many of the last lines or bytes as are wanted.
+fn tail_stream(
+    mut reader: impl BufRead,
+    out: &mut Output<impl Write>,
+    take: &TakeValue,
+    bytes: bool,
+    delimiter: u8,
+) -> Result<()> {
+    match *take {
+        TakeNum(num) if num <= 0 => {
+            let num = num.unsigned_abs() as usize;
+            let mut total = 0;
+            if bytes {
+                let mut last = VecDeque::new();
+                loop {
+                    let buf = reader.fill_buf()?;
+                    if buf.is_empty() {
+                        break;
+                    }
+                    let len = buf.len();
+                    last.extend(buf);
+                    reader.consume(len);
+                    total += len;
+                    if last.len() > num {
+                        last.drain(..last.len() - num);
+                    }
+                }
+                out.at((total - last.len()) as u64)?;
+                let (front, back) = last.as_slices();
+                out.write_all(front)?;
+                out.write_all(back)?;
             } else {
-                Some(num - 1)
+                let mut last: VecDeque<Vec<u8>> = VecDeque::new();
+                let mut line = Vec::new();
+                loop {
+                    line.clear();
+                    let len = reader.read_until(delimiter, &mut line)?;
+                    if len == 0 {
+                        break;
+                    }
+                    total += len;
+                    if num == 0 {
+                        continue;
+                    }
+                    // Reuse the buffer of the line that falls out
+                    let spare = if last.len() == num {
+                        last.pop_front()
+                    } else {
+                        None
+                    };
+                    last.push_back(std::mem::replace(&mut line, spare.unwrap_or_default()));
+                }
+                out.at((total - last.iter().map(Vec::len).sum::<usize>()) as u64)?;
+                for line in last {
+                    out.write_all(&line)?;
+                }
             }
         }
-        PlusZero => {
-            if total != 0 {
-                Some(0)
+        _ => {
+            let skip = match *take {
+                TakeNum(num) => num as u64 - 1,
+                PlusZero => 0,
+            };
+            let mut skipped = 0;
+            if bytes {
+                skipped = io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
             } else {
-                None
+                let mut line = Vec::new();
+                for _ in 0..skip {
+                    line.clear();
+                    let len = reader.read_until(delimiter, &mut line)?;
+                    if len == 0 {
+                        break;
+                    }
+                    skipped += len as u64;
+                }
             }
+            out.at(skipped)?;
+            io::copy(&mut reader, out)?;
         }
     }
+    Ok(())
 }
 
-fn print_header(i: usize, filename: &str) {
-    if i > 0 {
-        println!();
+/// Print the requested end of an open file. Returns the offset the file
+/// has been printed up to, or `None` for a pipe or the like, which has no
+/// offsets to follow.
+fn tail(mut file: File, args: &Args, out: &mut Output<impl Write>) -> Result<Option<u64>> {
+    let take = args.bytes.as_ref().unwrap_or(&args.lines);
+    let meta = file.metadata()?;
+    // Pipes cannot seek, and files in /proc claim to be empty, so read these
+    // through to the end
+    if !meta.is_file() || meta.len() == 0 {
+        tail_stream(
+            BufReader::new(file),
+            out,
+            take,
+            args.bytes.is_some(),
+            args.delimiter(),
+        )?;
+        return Ok(meta.is_file().then_some(0));
     }
-    println!("==> {} <==", filename);
-}
-
-fn print_lines(mut file: impl BufRead, num_lines: &TakeValue, total_lines: i64) -> Result<()> {
-    if let Some(start) = get_start_index(num_lines, total_lines) {
-        let mut line = String::new();
-        for i in 0..total_lines {
-            file.read_line(&mut line)?;
-            if i >= start {
-                print!("{}", line);
+    let size = meta.len();
+    let start = match *take {
+        // Counting from the end only needs the end of the file
+        TakeNum(num) if num <= 0 => {
+            let num = num.unsigned_abs();
+            if num == 0 {
+                size
+            } else if args.bytes.is_some() {
+                size.saturating_sub(num)
+            } else {
+                last_lines_start(&mut file, num, size, args.delimiter())?
             }
-            line.clear();
         }
+        // Counting from the start, bytes can be skipped over
+        TakeNum(num) if args.bytes.is_some() => (num as u64 - 1).min(size),
+        PlusZero if args.bytes.is_some() => 0,
+        // and lines read past on the way to those to print
+        _ => {
+            let mut reader = BufReader::new(file);
+            tail_stream(&mut reader, out, take, false, args.delimiter())?;
+            return Ok(Some(reader.stream_position()?));
+        }
+    };
+    if args.mmap {
+        // SAFETY: the map is only read here, while the file is open; if the
+        // file is truncated meanwhile, reading past its end raises SIGBUS
+        let map = unsafe {
+            MmapOptions::new()
+                .offset(start)
+                .len((size - start) as usize)
+                .map(&file)?
+        };
+        out.at(start)?;
+        out.write_all(&map)?;
+    } else {
+        print_range(file, start, size, out)?;
     }
-    Ok(())
+    Ok(Some(size))
 }
 
-fn print_bytes<T>(mut file: T, num_bytes: &TakeValue, total_bytes: i64) -> Result<()>
-where
-    T: Read + Seek,
-{
-    if let Some(start) = get_start_index(num_bytes, total_bytes) {
-        file.seek(SeekFrom::Start(start as u64))?;
-        let mut buf = vec![0; (total_bytes - start) as usize];
-        file.read_exact(&mut buf)?;
-        print!("{}", String::from_utf8_lossy(&buf));
+/// Print the end of every file, then follow them if asked. Files that
+/// cannot be read are reported on stderr, unless silenced, and skipped;
+/// returns how many there were.
+fn run(args: Args) -> Result<usize> {
+    let mut out = Output::new(io::stdout().lock(), args.delimiter());
+    if let Some(pattern) = &args.grep {
+        out.grep(pattern.clone(), args.grep_invert);
     }
-    Ok(())
-}
-
-fn run(args: Args) -> Result<()> {
-    for (i, filename) in args.files.iter().enumerate() {
-        let (total_lines, total_bytes) = count_lines_bytes(filename)?;
-        if args.files.len() > 1 && !args.quiet {
-            print_header(i, filename);
+    if let Some(pattern) = &args.highlight {
+        if use_color(args.color) {
+            out.highlight(pattern.clone());
         }
-        if let Some(bytes) = &args.bytes {
-            let file = open_file(filename)?;
-            print_bytes(file, bytes, total_bytes)?;
-        } else {
-            let file = open_bufread(filename)?;
-            print_lines(file, &args.lines, total_lines)?;
+    }
+    if args.format == Format::Json {
+        out.json();
+    }
+    let retry = args.retry || args.follow_name;
+    let mode = if args.follow_name {
+        Some(FollowMode::Name)
+    } else {
+        args.follow
+    };
+    let files = input_files(&args)?;
+    let headers = args.headers(files.len());
+    let mut follower = mode.map(|mode| {
+        let mut follower = Follower::new(mode, retry, args.sleep_interval);
+        follower.max_unchanged_stats(args.max_unchanged_stats);
+        if let Some(pid) = args.pid {
+            follower.until_exit(pid);
+        }
+        if !args.disable_inotify {
+            follower.use_inotify();
+        }
+        if headers {
+            follower.show_headers();
+        }
+        for pattern in args.patterns() {
+            follower.add_pattern(&pattern);
+        }
+        follower
+    });
+    let mut num_errors = 0;
+    for filename in &files {
+        if filename == "-" {
+            if headers {
+                out.header("standard input")?;
+            }
+            out.source(filename)?;
+            let take = args.bytes.as_ref().unwrap_or(&args.lines);
+            let result = tail_stream(
+                io::stdin().lock(),
+                &mut out,
+                take,
+                args.bytes.is_some(),
+                args.delimiter(),
+            );
+            if let Err(err) = result {
+                num_errors += 1;
+                if !args.silent {
+                    eprintln!("standard input: {}", err);
+                }
+            }
+            if let Some(follower) = &mut follower {
+                follower.printed("standard input");
+            }
+            continue;
+        }
+        let file = match open_file(filename) {
+            Ok(file) => file,
+            Err(err) => {
+                num_errors += 1;
+                if !args.silent {
+                    eprintln!("{}", err);
+                }
+                if let Some(follower) = &mut follower {
+                    follower.add(filename, None);
+                }
+                continue;
+            }
+        };
+        if headers {
+            out.header(filename)?;
+        }
+        out.source(filename)?;
+        match tail(file, &args, &mut out) {
+            Ok(printed) => {
+                if let Some(follower) = &mut follower {
+                    follower.printed(filename);
+                    if printed.is_some() {
+                        follower.add(filename, printed);
+                    }
+                }
+            }
+            Err(err) => {
+                num_errors += 1;
+                if !args.silent {
+                    eprintln!("{}: {}", filename, err);
+                }
+            }
         }
     }
-    Ok(())
+    if let Some(follower) = &mut follower {
+        follower.run(&mut out)?;
+    }
+    out.finish()?;
+    Ok(num_errors)
 }
 
 fn main() {
     let args = Args::parse();
-    if let Err(err) = run(args) {
-        eprintln!("{}", err);
+    match run(args) {
+        Ok(0) => {}
+        Ok(_) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
     }
 }
 
@@ -268,36 +714,66 @@ mod tests {
     }
 
     #[test]
-    fn test_count_lines_bytes() {
-        let res = count_lines_bytes("tests/inputs/one.txt");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), (1, 24));
-
-        let res = count_lines_bytes("tests/inputs/twelve.txt");
-        assert!(res.is_ok());
-        assert_eq!(res.unwrap(), (12, 63));
+    fn test_last_lines_start() {
+        let start = |text: &str, num| {
+            let mut file = io::Cursor::new(text);
+            last_lines_start(&mut file, num, text.len() as u64, b'\n').unwrap()
+        };
+        assert_eq!(start("", 1), 0);
+        assert_eq!(start("a\nb\nc\n", 1), 4);
+        assert_eq!(start("a\nb\nc\n", 2), 2);
+        assert_eq!(start("a\nb\nc\n", 3), 0);
+        assert_eq!(start("a\nb\nc\n", 4), 0);
+        assert_eq!(start("a\nb\nc", 1), 4);
+        assert_eq!(start("a\n\n\n", 2), 2);
+
+        // Lines spanning blocks
+        let text = format!("{}\n{}\nend\n", "x".repeat(100_000), "y".repeat(70_000));
+        assert_eq!(start(&text, 2), 100_001);
+        assert_eq!(start(&text, 3), 0);
+
+        let mut file = io::Cursor::new("a\0b\nc\0");
+        assert_eq!(last_lines_start(&mut file, 1, 6, b'\0').unwrap(), 2);
     }
 
     #[test]
-    fn test_get_start_index() {
-        assert_eq!(get_start_index(&PlusZero, 0), None);
-
-        assert_eq!(get_start_index(&PlusZero, 1), Some(0));
-
-        assert_eq!(get_start_index(&TakeNum(0), 1), None);
-
-        assert_eq!(get_start_index(&TakeNum(1), 0), None);
-
-        assert_eq!(get_start_index(&TakeNum(2), 1), None);
-
-        assert_eq!(get_start_index(&TakeNum(1), 10), Some(0));
-        assert_eq!(get_start_index(&TakeNum(2), 10), Some(1));
-        assert_eq!(get_start_index(&TakeNum(3), 10), Some(2));
-
-        assert_eq!(get_start_index(&TakeNum(-1), 10), Some(9));
-        assert_eq!(get_start_index(&TakeNum(-2), 10), Some(8));
-        assert_eq!(get_start_index(&TakeNum(-3), 10), Some(7));
-
-        assert_eq!(get_start_index(&TakeNum(-20), 10), Some(0));
+    fn test_tail_stream() {
+        let tail = |take: TakeValue, bytes: bool| {
+            let mut out = Output::new(vec![], b'\n');
+            tail_stream("a\nb\nc".as_bytes(), &mut out, &take, bytes, b'\n').unwrap();
+            String::from_utf8(out.into_inner()).unwrap()
+        };
+        assert_eq!(tail(TakeNum(-2), false), "b\nc");
+        assert_eq!(tail(TakeNum(-5), false), "a\nb\nc");
+        assert_eq!(tail(TakeNum(0), false), "");
+        assert_eq!(tail(TakeNum(2), false), "b\nc");
+        assert_eq!(tail(TakeNum(5), false), "");
+        assert_eq!(tail(PlusZero, false), "a\nb\nc");
+        assert_eq!(tail(TakeNum(-3), true), "b\nc");
+        assert_eq!(tail(TakeNum(0), true), "");
+        assert_eq!(tail(TakeNum(3), true), "b\nc");
+        assert_eq!(tail(PlusZero, true), "a\nb\nc");
+
+        let mut out = Output::new(vec![], b'\0');
+        tail_stream("a\0b\nc\0".as_bytes(), &mut out, &TakeNum(-1), false, b'\0').unwrap();
+        assert_eq!(out.into_inner(), b"b\nc\0");
+
+        // Offsets of what is printed
+        let offsets = |take: TakeValue, bytes: bool| {
+            let mut out = Output::new(vec![], b'\n');
+            out.json();
+            tail_stream("a\nbc\nd".as_bytes(), &mut out, &take, bytes, b'\n').unwrap();
+            out.finish().unwrap();
+            let out = String::from_utf8(out.into_inner()).unwrap();
+            out.lines()
+                .map(|line| {
+                    serde_json::from_str::<serde_json::Value>(line).unwrap()["offset"].clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(offsets(TakeNum(-2), false), [2, 5]);
+        assert_eq!(offsets(TakeNum(2), false), [2, 5]);
+        assert_eq!(offsets(TakeNum(-2), true), [4, 5]);
+        assert_eq!(offsets(TakeNum(4), true), [3, 5]);
     }
 }
@@ -0,0 +1,238 @@
+use ansi_term::{Colour, Style};
+use regex::bytes::Regex;
+use serde_json::json;
+use std::io::{self, Write};
+
+/// Everything tailr prints goes through here, so that headers go between
+/// files and lines can be filtered, highlighted or wrapped in JSON on the
+/// way out.
+pub struct Output<W: Write> {
+    out: W,
+    delimiter: u8,
+    grep: Option<(Regex, bool)>,
+    highlight: Option<(Regex, Style)>,
+    json: bool,
+    /// The start of a line whose end has not been seen yet
+    partial: Vec<u8>,
+    started: bool,
+    /// The file being printed
+    file: String,
+    /// Where in the file the next byte written comes from
+    offset: u64,
+    /// Where in the file the line being written starts
+    line_start: u64,
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(out: W, delimiter: u8) -> Self {
+        Self {
+            out,
+            delimiter,
+            grep: None,
+            highlight: None,
+            json: false,
+            partial: vec![],
+            started: false,
+            file: String::new(),
+            offset: 0,
+            line_start: 0,
+        }
+    }
+
+    /// Only print lines matching `pattern`, or with `invert` only those
+    /// that do not.
+    pub fn grep(&mut self, pattern: Regex, invert: bool) {
+        self.grep = Some((pattern, invert));
+    }
+
+    /// Color what matches `pattern`.
+    pub fn highlight(&mut self, pattern: Regex) {
+        self.highlight = Some((pattern, Colour::Red.bold()));
+    }
+
+    /// Print each line as a JSON object giving the file and offset it comes
+    /// from, instead of headers and raw lines.
+    pub fn json(&mut self) {
+        self.json = true;
+    }
+
+    /// Note that what is written next comes from the start of `filename`.
+    pub fn source(&mut self, filename: &str) -> io::Result<()> {
+        if filename != self.file {
+            self.finish()?;
+            self.file = filename.to_string();
+            (self.offset, self.line_start) = (0, 0);
+        }
+        Ok(())
+    }
+
+    /// Note that what is written next comes from `offset` in the file.
+    pub fn at(&mut self, offset: u64) -> io::Result<()> {
+        if offset != self.offset {
+            self.finish()?;
+            (self.offset, self.line_start) = (offset, offset);
+        }
+        Ok(())
+    }
+
+    /// Print the header naming the file that output comes from next, after
+    /// a blank line unless it starts the output.
+    pub fn header(&mut self, filename: &str) -> io::Result<()> {
+        self.finish()?;
+        if self.json {
+            return Ok(());
+        }
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        writeln!(self.out, "==> {} <==", filename)
+    }
+
+    /// Print any last line that ended without a delimiter.
+    pub fn finish(&mut self) -> io::Result<()> {
+        let line = std::mem::take(&mut self.partial);
+        self.line(&line)?;
+        self.line_start = self.offset;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn line(&mut self, line: &[u8]) -> io::Result<()> {
+        let text = line.strip_suffix(&[self.delimiter]).unwrap_or(line);
+        if let Some((pattern, invert)) = &self.grep {
+            if line.is_empty() || pattern.is_match(text) == *invert {
+                return Ok(());
+            }
+        }
+        if self.json {
+            if line.is_empty() {
+                return Ok(());
+            }
+            let record = json!({
+                "file": self.file,
+                "offset": self.line_start,
+                "line": String::from_utf8_lossy(text),
+            });
+            return writeln!(self.out, "{}", record);
+        }
+        let Some((pattern, style)) = &self.highlight else {
+            return self.out.write_all(line);
+        };
+        let mut pos = 0;
+        for found in pattern.find_iter(text).filter(|found| !found.is_empty()) {
+            self.out.write_all(&text[pos..found.start()])?;
+            write!(self.out, "{}", style.prefix())?;
+            self.out.write_all(found.as_bytes())?;
+            write!(self.out, "{}", style.suffix())?;
+            pos = found.end();
+        }
+        self.out.write_all(&line[pos..])
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.started |= !buf.is_empty();
+        if self.grep.is_none() && self.highlight.is_none() && !self.json {
+            return self.out.write(buf);
+        }
+        // Lines are only complete once their delimiter arrives
+        let mut rest = buf;
+        while let Some(end) = rest.iter().position(|&b| b == self.delimiter) {
+            let (line, after) = rest.split_at(end + 1);
+            if self.partial.is_empty() {
+                self.line(line)?;
+            } else {
+                let mut whole = std::mem::take(&mut self.partial);
+                whole.extend_from_slice(line);
+                self.line(&whole)?;
+            }
+            self.offset += line.len() as u64;
+            self.line_start = self.offset;
+            rest = after;
+        }
+        self.partial.extend_from_slice(rest);
+        self.offset += rest.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Output;
+    use regex::bytes::Regex;
+    use std::io::Write;
+
+    #[test]
+    fn test_grep() {
+        let grep = |invert: bool, chunks: &[&str]| {
+            let mut out = Output::new(vec![], b'\n');
+            out.grep(Regex::new("^b").unwrap(), invert);
+            for chunk in chunks {
+                out.write_all(chunk.as_bytes()).unwrap();
+            }
+            out.finish().unwrap();
+            String::from_utf8(out.out).unwrap()
+        };
+        assert_eq!(grep(false, &["a\nb1\nc\nb2"]), "b1\nb2");
+        assert_eq!(grep(true, &["a\nb1\nc\nb2"]), "a\nc\n");
+        // Lines split across writes
+        assert_eq!(grep(false, &["a\n", "b", "1\nb", "2\n"]), "b1\nb2\n");
+        assert_eq!(grep(true, &[""]), "");
+    }
+
+    #[test]
+    fn test_header() {
+        let mut out = Output::new(vec![], b'\n');
+        out.grep(Regex::new("x").unwrap(), false);
+        out.header("a").unwrap();
+        out.write_all(b"x1\ny").unwrap();
+        out.header("b").unwrap();
+        out.write_all(b"2x").unwrap();
+        out.finish().unwrap();
+        assert_eq!(out.out, b"==> a <==\nx1\n\n==> b <==\n2x");
+    }
+
+    #[test]
+    fn test_json() {
+        let mut out = Output::new(vec![], b'\n');
+        out.json();
+        out.header("a").unwrap();
+        out.source("a").unwrap();
+        out.at(10).unwrap();
+        out.write_all(b"x\n\"y\"\nz").unwrap();
+        out.write_all(b"z\n").unwrap();
+        out.source("b").unwrap();
+        out.write_all(b"\xff").unwrap();
+        out.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(out.out).unwrap(),
+            r#"{"file":"a","offset":10,"line":"x"}
+{"file":"a","offset":12,"line":"\"y\""}
+{"file":"a","offset":16,"line":"zz"}
+{"file":"b","offset":0,"line":"�"}
+"#
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let mut out = Output::new(vec![], b'\n');
+        out.highlight(Regex::new("o+|x*").unwrap());
+        out.write_all(b"foo bor\nbaz\no").unwrap();
+        out.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(out.out).unwrap(),
+            "f\x1b[1;31moo\x1b[0m b\x1b[1;31mo\x1b[0mr\nbaz\n\x1b[1;31mo\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::{
+    collections::HashMap,
+    fs, io,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+/// Wakes the follow loop when a followed file or its directory changes,
+/// through inotify, kqueue or whatever the platform offers.
+pub struct Watcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    /// The inode each watched path had when it was watched
+    watched: HashMap<PathBuf, u64>,
+}
+
+impl Watcher {
+    /// Returns `None` where the platform cannot watch files, so callers poll.
+    pub fn new() -> Option<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).ok()?;
+        Some(Self {
+            watcher,
+            events,
+            watched: HashMap::new(),
+        })
+    }
+
+    /// Watch the file at `path` for writes and its directory for files
+    /// coming and going. Watching the same file again is harmless, and
+    /// watches a new file that has replaced it. Returns false if either
+    /// could not be watched.
+    pub fn watch(&mut self, path: &str) -> bool {
+        let path = Path::new(path);
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let dir = self.add_watch(dir);
+        let file = self.add_watch(path);
+        file && dir
+    }
+
+    fn add_watch(&mut self, path: &Path) -> bool {
+        let Ok(ino) = fs::metadata(path).map(|metadata| metadata.ino()) else {
+            return false;
+        };
+        match self.watched.get(path) {
+            Some(&watched) if watched == ino => return true,
+            Some(_) => {
+                // A watch follows the file, not its name
+                let _ = self.watcher.unwatch(path);
+                self.watched.remove(path);
+            }
+            None => {}
+        }
+        let ok = self
+            .watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .is_ok();
+        if ok {
+            self.watched.insert(path.to_path_buf(), ino);
+        }
+        ok
+    }
+
+    /// Block until there are events or `timeout` passes, then discard
+    /// the events: the caller checks every file anyway.
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let first = match timeout {
+            Some(timeout) => self.events.recv_timeout(timeout),
+            None => self
+                .events
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        if let Err(RecvTimeoutError::Disconnected) = first {
+            return Err(io::Error::other("file watcher stopped"));
+        }
+        while self.events.try_recv().is_ok() {}
+        Ok(())
+    }
+}
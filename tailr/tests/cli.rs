@@ -3,8 +3,12 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
-use std::fs::{self, File};
-use std::io::Read;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{self, Child, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const PRG: &str = "tailr";
 const EMPTY: &str = "tests/inputs/empty.txt";
@@ -12,6 +16,7 @@ const ONE: &str = "tests/inputs/one.txt";
 const TWO: &str = "tests/inputs/two.txt";
 const THREE: &str = "tests/inputs/three.txt";
 const TWELVE: &str = "tests/inputs/twelve.txt";
+const RECORDS: &str = "tests/inputs/records.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -34,11 +39,13 @@ fn gen_bad_file() -> String {
 
 // --------------------------------------------------
 #[test]
-fn dies_no_args() -> Result<()> {
+fn no_args_reads_stdin() -> Result<()> {
     Command::cargo_bin(PRG)?
+        .args(["-n", "1"])
+        .write_stdin("a\nb\n")
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Usage"));
+        .success()
+        .stdout("b\n");
 
     Ok(())
 }
@@ -94,11 +101,42 @@ fn skips_bad_file() -> Result<()> {
     Command::cargo_bin(PRG)?
         .args([ONE, &bad, TWO])
         .assert()
+        .failure()
+        .stdout(predicate::str::contains("==> tests/inputs/two.txt <=="))
         .stderr(predicate::str::is_match(expected)?);
 
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn skips_unreadable_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "tests/inputs", ONE])
+        .assert()
+        .failure()
+        .stdout("==> tests/inputs <==\n\n==> tests/inputs/one.txt <==\nÖne line, four wordś.\n")
+        .stderr(predicate::str::starts_with("tests/inputs: Is a directory"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn silent() -> Result<()> {
+    let bad = gen_bad_file();
+    for flag in ["-s", "--silent"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-n", "1", &bad, ONE])
+            .assert()
+            .code(1)
+            .stdout("==> tests/inputs/one.txt <==\nÖne line, four wordś.\n")
+            .stderr("");
+    }
+
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> Result<()> {
     // Extra work here due to lossy UTF
@@ -114,6 +152,296 @@ fn run(args: &[&str], expected_file: &str) -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> Result<()> {
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&expected)
+    );
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_lines() -> Result<()> {
+    for (arg, out) in [("3", "n3"), ("+2", "n+2"), ("0", "n0"), ("+0", "n+0")] {
+        run_stdin(
+            &["-n", arg],
+            TWELVE,
+            &format!("tests/expected/twelve.txt.{out}.out"),
+        )?;
+    }
+    run_stdin(&["-n", "200", "-"], ONE, "tests/expected/one.txt.n200.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_bytes() -> Result<()> {
+    for (arg, out) in [("8", "c8"), ("+2", "c+2"), ("+0", "c+0")] {
+        run_stdin(
+            &["-c", arg],
+            TWELVE,
+            &format!("tests/expected/twelve.txt.{out}.out"),
+        )?;
+    }
+    run_stdin(&["-c", "3", "-"], ONE, "tests/expected/one.txt.c3.out")
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn fifo() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("tailr-{}", random_string()));
+    fs::create_dir(&dir)?;
+    let fifo = dir.join("fifo");
+    assert!(process::Command::new("mkfifo")
+        .arg(&fifo)
+        .status()?
+        .success());
+    let input = fs::read(TWELVE)?;
+    let cases = [("-n", "3", "n3"), ("-n", "+2", "n+2"), ("-c", "8", "c8")];
+    for (opt, num, out) in cases {
+        // Opening a FIFO blocks until the other end is opened too
+        let writer = {
+            let (fifo, input) = (fifo.clone(), input.clone());
+            thread::spawn(move || fs::write(fifo, input))
+        };
+        run(
+            &[opt, num, fifo.to_str().unwrap()],
+            &format!("tests/expected/twelve.txt.{out}.out"),
+        )?;
+        writer.join().unwrap()?;
+    }
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(target_os = "linux")]
+fn proc_file() -> Result<()> {
+    // Files in /proc report a size of 0
+    let version = fs::read("/proc/version")?;
+    Command::cargo_bin(PRG)?
+        .args(["-c", "8", "/proc/version"])
+        .assert()
+        .success()
+        .stdout(predicate::eq(&version[version.len() - 8..]));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grep() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "6", TWELVE, "--grep", "^t"])
+        .assert()
+        .success()
+        .stdout("ten\ntwelve\n");
+    Command::cargo_bin(PRG)?
+        .args(["-n", "6", TWELVE, "--grep", "^t", "--grep-invert"])
+        .assert()
+        .success()
+        .stdout("seven\neight\nnine\neleven\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grep_keeps_headers() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "2", TWELVE, EMPTY, ONE, "--grep", "e$"])
+        .assert()
+        .success()
+        .stdout(
+            "==> tests/inputs/twelve.txt <==\ntwelve\n\n\
+             ==> tests/inputs/empty.txt <==\n\n\
+             ==> tests/inputs/one.txt <==\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn highlight() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "2", TWELVE, "--highlight", "e+", "--color"])
+        .assert()
+        .success()
+        .stdout(
+            "\x1b[1;31me\x1b[0ml\x1b[1;31me\x1b[0mv\x1b[1;31me\x1b[0mn\n\
+             tw\x1b[1;31me\x1b[0mlv\x1b[1;31me\x1b[0m\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn highlight_only_with_color() -> Result<()> {
+    for args in [
+        &["--highlight", "e"][..],
+        &["--highlight=e", "--color=never"],
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(["-n", "2", TWELVE])
+            .args(args)
+            .assert()
+            .success()
+            .stdout("eleven\ntwelve\n");
+    }
+    Command::cargo_bin(PRG)?
+        .args(["-n", "2", TWELVE, "--highlight=e", "--color=always"])
+        .env("NO_COLOR", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[1;31m"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn format_json() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--format", "json", "-n", "2", TWELVE, ONE])
+        .assert()
+        .success()
+        .stdout(
+            r#"{"file":"tests/inputs/twelve.txt","offset":49,"line":"eleven"}
+{"file":"tests/inputs/twelve.txt","offset":56,"line":"twelve"}
+{"file":"tests/inputs/one.txt","offset":0,"line":"Öne line, four wordś."}
+"#,
+        );
+    Command::cargo_bin(PRG)?
+        .args(["--format=json", "-n", "+2", "--grep", "b"])
+        .write_stdin("a\nb\nc\nb")
+        .assert()
+        .success()
+        .stdout(
+            r#"{"file":"-","offset":2,"line":"b"}
+{"file":"-","offset":6,"line":"b"}
+"#,
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_grep() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([ONE, "--grep", "("])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '(' for '--grep <PATTERN>'",
+        ));
+    Command::cargo_bin(PRG)?
+        .args([ONE, "--grep-invert"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--grep <PATTERN>"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn directory() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "--directory", "tests/inputs/", ONE])
+        .assert()
+        .success()
+        .stdout(
+            "==> tests/inputs/one.txt <==\nÖne line, four wordś.\n\n\
+             ==> tests/inputs/empty.txt <==\n\n\
+             ==> tests/inputs/one.txt <==\nÖne line, four wordś.\n\n\
+             ==> tests/inputs/records.txt <==\nlines\0three\0four\0\n\
+             ==> tests/inputs/three.txt <==\nfour words.\n\n\
+             ==> tests/inputs/twelve.txt <==\ntwelve\n\n\
+             ==> tests/inputs/two.txt <==\nFour words.\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn glob() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "--glob", "tests/inputs/t*.txt", "-"])
+        .write_stdin("in\n")
+        .assert()
+        .success()
+        .stdout(
+            "==> tests/inputs/three.txt <==\nfour words.\n\n\
+             ==> tests/inputs/twelve.txt <==\ntwelve\n\n\
+             ==> tests/inputs/two.txt <==\nFour words.\n\n\
+             ==> standard input <==\nin\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_directory() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--directory", ONE])
+        .assert()
+        .stderr("tests/inputs/one.txt: Not a directory\n");
+    Command::cargo_bin(PRG)?
+        .args(["--glob", "tests/inputs/[.txt"])
+        .assert()
+        .stderr(predicate::str::starts_with(
+            "tests/inputs/[.txt: Pattern syntax error",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap() -> Result<()> {
+    for (opt, num, out) in [("-c", "8", "c8"), ("-c", "200", "c200"), ("-n", "3", "n3")] {
+        run(
+            &["--mmap", opt, num, TWELVE],
+            &format!("tests/expected/twelve.txt.{out}.out"),
+        )?;
+    }
+    run(&["--mmap", "-c", "3", ONE], "tests/expected/one.txt.c3.out")?;
+    run(&["--mmap", EMPTY], "tests/expected/empty.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn zero_terminated() -> Result<()> {
+    for num in ["2", "+2", "1"] {
+        let expected = format!("tests/expected/records.txt.z.n{num}.out");
+        run(&["-z", "-n", num, RECORDS], &expected)?;
+        run_stdin(&["--zero-terminated", "-n", num], RECORDS, &expected)?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_among_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", ONE, "-"])
+        .write_stdin("a\nb\n")
+        .assert()
+        .success()
+        .stdout(
+            "==> tests/inputs/one.txt <==\nÖne line, four wordś.\n\n==> standard input <==\nb\n",
+        );
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn empty() -> Result<()> {
@@ -757,6 +1085,44 @@ fn multiple_files_n1_quiet() -> Result<()> {
     )
 }
 
+#[test]
+fn multiple_files_n1_quiet_verbose() -> Result<()> {
+    run(
+        &["-n", "1", "-q", "-v", TWELVE, EMPTY, ONE, THREE, TWO],
+        "tests/expected/all.n1.out",
+    )?;
+    run(
+        &[
+            "-n",
+            "1",
+            "--verbose",
+            "--quiet",
+            TWELVE,
+            EMPTY,
+            ONE,
+            THREE,
+            TWO,
+        ],
+        "tests/expected/all.n1.q.out",
+    )
+}
+
+#[test]
+fn verbose_one_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-v", "-n", "2", TWELVE])
+        .assert()
+        .success()
+        .stdout("==> tests/inputs/twelve.txt <==\neleven\ntwelve\n");
+    Command::cargo_bin(PRG)?
+        .args(["--verbose", "-c", "3", "-"])
+        .write_stdin("abcd")
+        .assert()
+        .success()
+        .stdout("==> standard input <==\nbcd");
+    Ok(())
+}
+
 #[test]
 fn multiple_files_n_minus_1() -> Result<()> {
     run(
@@ -828,3 +1194,391 @@ fn multiple_files_c_plus_3() -> Result<()> {
         "tests/expected/all.c+3.out",
     )
 }
+
+// --------------------------------------------------
+/// A tailr process following files in a scratch directory, with its output
+/// going to files there.
+struct Follow {
+    dir: PathBuf,
+    child: Child,
+}
+
+impl Follow {
+    fn start(args: &[&str], files: &[(&str, &str)]) -> Result<Follow> {
+        let dir = std::env::temp_dir().join(format!("tailr-{}", random_string()));
+        fs::create_dir(&dir)?;
+        for (name, contents) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+        let mut cmd = process::Command::new(assert_cmd::cargo::cargo_bin(PRG));
+        if !args.contains(&"--sleep-interval") {
+            cmd.args(["--sleep-interval", "0.05"]);
+        }
+        let child = cmd
+            .args(args)
+            .current_dir(&dir)
+            .stdout(Stdio::from(File::create(dir.join("stdout"))?))
+            .stderr(Stdio::from(File::create(dir.join("stderr"))?))
+            .spawn()?;
+        Ok(Follow { dir, child })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn append(&self, name: &str, text: &str) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(name))?;
+        Ok(file.write_all(text.as_bytes())?)
+    }
+
+    /// Wait for the stdout or stderr file to end with `expected`.
+    fn wait_for(&self, output: &str, expected: &str) -> Result<String> {
+        let start = Instant::now();
+        loop {
+            let text = fs::read_to_string(self.path(output))?;
+            if text.ends_with(expected) || start.elapsed() > Duration::from_secs(5) {
+                return Ok(text);
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for Follow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn settle() {
+    thread::sleep(Duration::from_millis(200));
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_appended() -> Result<()> {
+    let follow = Follow::start(&["-f", "-n", "1", "log"], &[("log", "a\nb\n")])?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "b\n");
+    follow.append("log", "c\nd")?;
+    assert_eq!(follow.wait_for("stdout", "c\nd")?, "b\nc\nd");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_multiple_files() -> Result<()> {
+    let follow = Follow::start(&["-f", "a", "b"], &[("a", "1\n"), ("b", "2\n")])?;
+    let start = "==> a <==\n1\n\n==> b <==\n2\n";
+    assert_eq!(follow.wait_for("stdout", "2\n")?, start);
+    follow.append("b", "3\n")?;
+    settle();
+    follow.append("a", "4\n")?;
+    settle();
+    follow.append("a", "5\n")?;
+    settle();
+    follow.append("b", "6\n")?;
+    assert_eq!(
+        follow.wait_for("stdout", "6\n")?,
+        format!("{start}3\n\n==> a <==\n4\n5\n\n==> b <==\n6\n")
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_grep() -> Result<()> {
+    let args = ["-f", "a", "b", "--grep", "ERROR"];
+    let follow = Follow::start(&args, &[("a", "ERROR 1\nok\n"), ("b", "ok\n")])?;
+    let start = "==> a <==\nERROR 1\n\n==> b <==\n";
+    assert_eq!(follow.wait_for("stdout", "==> b <==\n")?, start);
+    follow.append("a", "ok\nERR")?;
+    settle();
+    follow.append("a", "OR 2\n")?;
+    assert_eq!(
+        follow.wait_for("stdout", "2\n")?,
+        format!("{start}\n==> a <==\nERROR 2\n")
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_format_json() -> Result<()> {
+    let args = ["-f", "--format", "json", "a", "b"];
+    let follow = Follow::start(&args, &[("a", "1\n"), ("b", "2\n")])?;
+    follow.wait_for(
+        "stdout",
+        "}\n{\"file\":\"b\",\"offset\":0,\"line\":\"2\"}\n",
+    )?;
+    follow.append("a", "3\n4")?;
+    settle();
+    follow.append("a", "5\n")?;
+    assert_eq!(
+        follow.wait_for("stdout", "\"45\"}\n")?,
+        r#"{"file":"a","offset":0,"line":"1"}
+{"file":"b","offset":0,"line":"2"}
+{"file":"a","offset":2,"line":"3"}
+{"file":"a","offset":4,"line":"45"}
+"#
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_directory() -> Result<()> {
+    let args = ["-f", "-n", "1", "--directory", "logs"];
+    let follow = Follow::start(&args, &[("logs/a", "1\n2\n")])?;
+    assert_eq!(follow.wait_for("stdout", "2\n")?, "==> logs/a <==\n2\n");
+    follow.append("logs/b", "3\n4\n")?;
+    assert_eq!(
+        follow.wait_for("stdout", "4\n")?,
+        "==> logs/a <==\n2\n\n==> logs/b <==\n3\n4\n"
+    );
+    assert_eq!(
+        follow.wait_for("stderr", "new file\n")?,
+        "'logs/b' has appeared;  following new file\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_glob() -> Result<()> {
+    let args = ["-f", "--glob", "logs/*.log"];
+    let follow = Follow::start(&args, &[("logs/a.txt", "a\n")])?;
+    follow.append("logs/b.txt", "b\n")?;
+    follow.append("logs/c.log", "c\n")?;
+    assert_eq!(follow.wait_for("stdout", "c\n")?, "==> logs/c.log <==\nc\n");
+    follow.append("logs/c.log", "d\n")?;
+    assert_eq!(
+        follow.wait_for("stdout", "d\n")?,
+        "==> logs/c.log <==\nc\nd\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_multiple_files_quiet() -> Result<()> {
+    let follow = Follow::start(&["-f", "-q", "a", "b"], &[("a", "1\n"), ("b", "2\n")])?;
+    follow.wait_for("stdout", "2\n")?;
+    follow.append("a", "3\n")?;
+    assert_eq!(follow.wait_for("stdout", "3\n")?, "1\n2\n3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_from_start() -> Result<()> {
+    for (args, start) in [
+        (["-n", "+2"], "b\n"),
+        (["-c", "+3"], "b\n"),
+        (["-c", "+9"], ""),
+    ] {
+        let follow = Follow::start(&[&args[..], &["-f", "log"]].concat(), &[("log", "a\nb\n")])?;
+        settle();
+        follow.append("log", "c\n")?;
+        assert_eq!(follow.wait_for("stdout", "c\n")?, format!("{start}c\n"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_truncated() -> Result<()> {
+    let follow = Follow::start(&["-f", "log"], &[("log", "a\nb\n")])?;
+    follow.wait_for("stdout", "b\n")?;
+    fs::write(follow.path("log"), "")?;
+    settle();
+    follow.append("log", "c\n")?;
+    assert_eq!(follow.wait_for("stdout", "c\n")?, "a\nb\nc\n");
+    assert_eq!(
+        follow.wait_for("stderr", "truncated\n")?,
+        "log: file truncated\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_descriptor_after_rename() -> Result<()> {
+    let follow = Follow::start(&["-f", "log"], &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    fs::rename(follow.path("log"), follow.path("log.1"))?;
+    follow.append("log", "new\n")?;
+    follow.append("log.1", "b\n")?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "a\nb\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_rotated() -> Result<()> {
+    let follow = Follow::start(&["-F", "log"], &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    // Rotate without a moment when there is no log
+    fs::hard_link(follow.path("log"), follow.path("log.1"))?;
+    follow.append("log.new", "b\n")?;
+    fs::rename(follow.path("log.new"), follow.path("log"))?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "a\nb\n");
+    assert_eq!(
+        follow.wait_for("stderr", "new file\n")?,
+        "'log' has been replaced;  following new file\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_rotated_after_append() -> Result<()> {
+    let polling = [
+        "--follow=name",
+        "--disable-inotify",
+        "--max-unchanged-stats",
+        "1",
+    ];
+    for args in [&["-F"][..], &polling] {
+        let follow = Follow::start(&[args, &["log"]].concat(), &[("log", "one\n")])?;
+        follow.wait_for("stdout", "one\n")?;
+        // What the old file got just before the rotation is still printed
+        follow.append("log", "last\n")?;
+        fs::rename(follow.path("log"), follow.path("log.1"))?;
+        follow.append("log", "fresh\n")?;
+        assert_eq!(follow.wait_for("stdout", "fresh\n")?, "one\nlast\nfresh\n");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_polling() -> Result<()> {
+    let args = [
+        "--follow=name",
+        "--disable-inotify",
+        "--max-unchanged-stats",
+        "2",
+        "log",
+    ];
+    let follow = Follow::start(&args, &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    fs::rename(follow.path("log"), follow.path("log.1"))?;
+    follow.append("log", "b\n")?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "a\nb\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_max_unchanged_stats() -> Result<()> {
+    let args = [
+        "--follow=name",
+        "--disable-inotify",
+        "--max-unchanged-stats=1000",
+        "log",
+    ];
+    let follow = Follow::start(&args, &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    // The name is not checked again while the open file keeps growing
+    fs::rename(follow.path("log"), follow.path("log.1"))?;
+    follow.append("log", "new\n")?;
+    follow.append("log.1", "old\n")?;
+    assert_eq!(follow.wait_for("stdout", "old\n")?, "a\nold\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_follow() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--follow=inode", ONE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value 'inode' for '--follow[=<HOW>]'",
+        ));
+    Command::cargo_bin(PRG)?
+        .args([ONE, "--max-unchanged-stats", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--max-unchanged-stats <N>"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_name_retry() -> Result<()> {
+    let follow = Follow::start(&["-F", "log"], &[])?;
+    follow.wait_for("stderr", "(os error 2)\n")?;
+    follow.append("log", "a\n")?;
+    assert_eq!(follow.wait_for("stdout", "a\n")?, "a\n");
+    fs::remove_file(follow.path("log"))?;
+    let stderr = follow.wait_for("stderr", "(os error 2)\n")?;
+    assert!(stderr.contains("'log' has appeared;  following new file\n"));
+    assert!(
+        stderr.ends_with("'log' has become inaccessible: No such file or directory (os error 2)\n")
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_until_pid_exits() -> Result<()> {
+    let mut sleeper = process::Command::new("sleep").arg("0.5").spawn()?;
+    let pid = sleeper.id().to_string();
+    // Reap the sleeper so it does not linger as a zombie
+    let reaper = thread::spawn(move || sleeper.wait());
+    Command::cargo_bin(PRG)?
+        .args(["-f", "--pid", &pid, "--sleep-interval", "0.05", ONE])
+        .timeout(Duration::from_secs(5))
+        .assert()
+        .success()
+        .stdout("Öne line, four wordś.\n");
+    reaper.join().unwrap()?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_pid_gone() -> Result<()> {
+    let mut done = process::Command::new("true").spawn()?;
+    done.wait()?;
+    Command::cargo_bin(PRG)?
+        .args(["-f", "--pid", &done.id().to_string(), ONE])
+        .timeout(Duration::from_secs(5))
+        .assert()
+        .success();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_inotify_wakes_early() -> Result<()> {
+    // Polling every 60 seconds would miss the 5 second wait
+    let follow = Follow::start(&["-F", "--sleep-interval", "60", "log"], &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    follow.append("log", "b\n")?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "a\nb\n");
+    fs::rename(follow.path("log"), follow.path("log.1"))?;
+    follow.append("log", "c\n")?;
+    assert_eq!(follow.wait_for("stdout", "c\n")?, "a\nb\nc\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_disable_inotify() -> Result<()> {
+    let follow = Follow::start(&["-F", "--disable-inotify", "log"], &[("log", "a\n")])?;
+    follow.wait_for("stdout", "a\n")?;
+    follow.append("log", "b\n")?;
+    assert_eq!(follow.wait_for("stdout", "b\n")?, "a\nb\n");
+    Ok(())
+}
@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+
+# Compare uniqr with GNU uniq on a sorted file of RUNS runs of 1 to 4 equal
+# lines, 57 bytes each. The default of 15000000 runs makes about 2.1 GB;
+# set RUNS for a quicker run, e.g. RUNS=1000000 ./bench.sh for 140 MB.
+RUNS=${RUNS:-15000000}
+SORTED=$(mktemp)
+trap 'rm -f "$SORTED"' EXIT
+
+awk -v runs="$RUNS" 'BEGIN {
+    for (i = 0; i < runs; i++) {
+        line = sprintf("line %08d of the benchmark input, padded out a little", i)
+        for (j = 0; j <= i % 4; j++) print line
+    }
+}' > "$SORTED"
+ls -lh "$SORTED" | awk '{ print "input: " $5 }'
+
+cargo build --release --quiet || exit 1
+UNIQR=target/release/uniqr
+
+for ARGS in "" "-c" "-d" "-u" "-f 1"; do
+    echo "== uniq $ARGS"
+    echo -n "uniq: "
+    { time uniq $ARGS "$SORTED" > /dev/null; } 2>&1 | grep real
+    echo -n "uniqr:"
+    { time $UNIQR $ARGS "$SORTED" > /dev/null; } 2>&1 | grep real
+done
@@ -19,4 +19,45 @@ for FILE in $ROOT/*.txt; do
     uniq -c   $FILE > ${OUT_DIR}/${BASENAME}.c.out
     uniq    < $FILE > ${OUT_DIR}/${BASENAME}.stdin.out
     uniq -c < $FILE > ${OUT_DIR}/${BASENAME}.stdin.c.out
+    uniq -d   $FILE > ${OUT_DIR}/${BASENAME}.d.out
+    uniq -u   $FILE > ${OUT_DIR}/${BASENAME}.u.out
 done
+
+FIELDS="$ROOT/fields.txt"
+uniq -f 1 $FIELDS > $OUT_DIR/fields.txt.f1.out
+uniq -f 2 $FIELDS > $OUT_DIR/fields.txt.f2.out
+
+# -s and -w count characters, unlike GNU uniq, so chars.txt.s1w5.out is kept by hand
+CHARS="$ROOT/chars.txt"
+uniq -s 1 $CHARS > $OUT_DIR/chars.txt.s1.out
+uniq -w 2 $CHARS > $OUT_DIR/chars.txt.w2.out
+
+for FILE in $ROOT/three.txt $ROOT/skip.txt; do
+    BASENAME=$(basename "$FILE")
+    uniq -D                       $FILE > ${OUT_DIR}/${BASENAME}.D.out
+    uniq --all-repeated=prepend   $FILE > ${OUT_DIR}/${BASENAME}.D-prepend.out
+    uniq --all-repeated=separate  $FILE > ${OUT_DIR}/${BASENAME}.D-separate.out
+done
+
+# GNU uniq pads counts to 7 columns, matched by --count-width 7
+for FILE in $ROOT/three.txt $ROOT/skip.txt; do
+    BASENAME=$(basename "$FILE")
+    uniq -c $FILE > ${OUT_DIR}/${BASENAME}.c7.out
+done
+
+# --global keeps the first of every line wherever it occurs; the count, -d
+# and -u variants are kept by hand
+echo -ne "b\na\nb\nc\na\nb\n" > $ROOT/global.txt
+awk '!seen[$0]++' $ROOT/global.txt > $OUT_DIR/global.txt.g.out
+
+# --merge reads several sorted files like sort -m
+echo -ne "apple\nbanana\nbanana\ncherry\n" > $ROOT/merge1.txt
+echo -ne "apple\ncherry\ndate\n"           > $ROOT/merge2.txt
+echo -ne "banana\ndate\nelder"             > $ROOT/merge3.txt
+MERGE="$ROOT/merge1.txt $ROOT/merge2.txt $ROOT/merge3.txt"
+sort -m $MERGE | uniq    > $OUT_DIR/merge.out
+sort -m $MERGE | uniq -c > $OUT_DIR/merge.c7.out
+
+# --squeeze-space and --ignore-blank-lines have no uniq equivalent, so
+# loose.txt.*.out are kept by hand
+echo -ne "a  b\n a b\n\na\tb\n\nc\n  \nc d\nc   d\n" > $ROOT/loose.txt
@@ -1,10 +1,26 @@
-use anyhow::{Error, Result};
-use clap::Parser;
+mod merge;
+
+use anyhow::{bail, Error, Result};
+use clap::{Parser, ValueEnum};
+use merge::MergeReader;
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader, Write},
+    borrow::Cow,
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
 };
 
+/// How `--all-repeated` delimits groups of duplicate lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Delimit {
+    /// No delimiting
+    None,
+    /// An empty line before each group
+    Prepend,
+    /// An empty line between groups
+    Separate,
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about = "Rust uniq")]
 pub struct Config {
@@ -12,13 +28,91 @@ pub struct Config {
     #[arg(value_name = "IN_FILE", default_value = "-")]
     in_file: String,
 
-    /// Output file
+    /// Output file, or stdout when - or not given
     #[arg(value_name = "OUT_FILE")]
     out_file: Option<String>,
 
+    /// Merge the sorted FILEs, like sort -m, and read them in place of IN_FILE
+    #[arg(
+        short = 'm',
+        long = "merge",
+        value_name = "FILE",
+        num_args = 1..,
+        conflicts_with_all = ["in_file", "out_file"]
+    )]
+    merge: Vec<String>,
+
+    /// Fail rather than overwrite an existing OUT_FILE
+    #[arg(short = 'n', long = "no-clobber")]
+    no_clobber: bool,
+
     /// Show counts
     #[arg(short = 'c', long = "count")]
     count: bool,
+
+    /// Right-align counts in a column WIDTH wide; GNU uniq uses 7
+    #[arg(
+        long = "count-width",
+        value_name = "WIDTH",
+        default_value = "4",
+        requires = "count"
+    )]
+    count_width: usize,
+
+    /// Only print duplicate lines, one for each group
+    #[arg(short = 'd', long = "repeated")]
+    repeated: bool,
+
+    /// Only print lines that are not repeated
+    #[arg(short = 'u', long = "unique")]
+    unique: bool,
+
+    /// Print all duplicate lines, delimiting groups with METHOD
+    #[arg(
+        short = 'D',
+        long = "all-repeated",
+        value_name = "METHOD",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "none",
+        conflicts_with = "count"
+    )]
+    all_repeated: Option<Delimit>,
+
+    /// Avoid comparing the first N fields
+    #[arg(
+        short = 'f',
+        long = "skip-fields",
+        value_name = "N",
+        default_value_t = 0
+    )]
+    skip_fields: usize,
+
+    /// Avoid comparing the first N characters
+    #[arg(
+        short = 's',
+        long = "skip-chars",
+        value_name = "N",
+        default_value_t = 0
+    )]
+    skip_chars: usize,
+
+    /// Compare no more than N characters
+    #[arg(short = 'w', long = "check-chars", value_name = "N")]
+    check_chars: Option<usize>,
+
+    /// Remove duplicates anywhere in the input, not only adjacent ones
+    #[arg(short = 'g', long = "global", conflicts_with = "all_repeated")]
+    global: bool,
+
+    /// Compare runs of whitespace as a single space, ignoring leading whitespace
+    #[arg(long = "squeeze-space")]
+    squeeze_space: bool,
+
+    /// Skip lines that are empty or only whitespace; they neither print nor separate duplicates
+    #[arg(long = "ignore-blank-lines")]
+    ignore_blank_lines: bool,
 }
 
 pub fn get_args() -> Result<Config> {
@@ -33,49 +127,295 @@ fn open(filename: &str) -> Result<Box<dyn BufRead>> {
     }
 }
 
-fn print_format(
-    out_file: &mut Box<dyn Write>,
-    show_count: bool,
+/// Options controlling which lines are compared equal and what is printed.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Prefix each line with its count, right-aligned in a column this wide.
+    pub count: Option<usize>,
+    /// Only print lines that are repeated.
+    pub repeated: bool,
+    /// Only print lines that are not repeated.
+    pub unique: bool,
+    /// Print every line of each repeated group, delimited as given.
+    pub all_repeated: Option<Delimit>,
+    /// Number of leading fields ignored when comparing.
+    pub skip_fields: usize,
+    /// Number of characters ignored after the skipped fields.
+    pub skip_chars: usize,
+    /// Compare no more than this many characters.
+    pub check_chars: Option<usize>,
+    /// Remove duplicates anywhere in the input, not only adjacent ones.
+    pub global: bool,
+    /// Compare runs of whitespace as a single space.
+    pub squeeze_space: bool,
+    /// Skip lines that are empty or only whitespace.
+    pub ignore_blank_lines: bool,
+}
+
+impl Options {
+    fn new(config: &Config) -> Self {
+        Self {
+            count: config.count.then_some(config.count_width),
+            repeated: config.repeated,
+            unique: config.unique,
+            all_repeated: config.all_repeated,
+            skip_fields: config.skip_fields,
+            skip_chars: config.skip_chars,
+            check_chars: config.check_chars,
+            global: config.global,
+            squeeze_space: config.squeeze_space,
+            ignore_blank_lines: config.ignore_blank_lines,
+        }
+    }
+}
+
+fn is_blank(byte: &u8) -> bool {
+    *byte == b' ' || *byte == b'\t'
+}
+
+/// Byte offset of the `n`th UTF-8 character in `bytes`, or its length when it
+/// is shorter. Invalid bytes count as one character each.
+fn char_offset(bytes: &[u8], n: usize) -> usize {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b & 0b1100_0000 != 0b1000_0000)
+        .nth(n)
+        .map_or(bytes.len(), |(i, _)| i)
+}
+
+/// The part of `line` that is compared: trailing whitespace is ignored,
+/// `skip_fields` leading fields, each blanks followed by non-blanks, and then
+/// `skip_chars` characters are skipped, and at most `check_chars` characters
+/// of the rest are kept. With `squeeze_space` that is then squeezed.
+fn key<'a>(options: &Options, line: &'a [u8]) -> Cow<'a, [u8]> {
+    let end = line
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    let mut key = &line[..end];
+    for _ in 0..options.skip_fields {
+        let start = key.iter().position(|b| !is_blank(b)).unwrap_or(key.len());
+        key = &key[start..];
+        let start = key.iter().position(is_blank).unwrap_or(key.len());
+        key = &key[start..];
+    }
+    key = &key[char_offset(key, options.skip_chars)..];
+    if let Some(check_chars) = options.check_chars {
+        key = &key[..char_offset(key, check_chars)];
+    }
+    if options.squeeze_space {
+        Cow::Owned(squeeze(key))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// `bytes` without leading or trailing whitespace, and with every other run of
+/// whitespace replaced by a single space.
+fn squeeze(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .split(u8::is_ascii_whitespace)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(&b' ')
+}
+
+/// Whether `line` is skipped by `--ignore-blank-lines`.
+fn skipped(options: &Options, line: &[u8]) -> bool {
+    options.ignore_blank_lines && line.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Whether a group of `counter` adjacent identical lines is printed. With both
+/// `-d` and `-u` no group qualifies.
+fn selected(options: &Options, counter: usize) -> bool {
+    let repeated = options.repeated || options.all_repeated.is_some();
+    (!repeated || counter > 1) && (!options.unique || counter == 1)
+}
+
+/// Print a group of `counter` identical lines if it is selected: `first` and,
+/// with `--all-repeated`, the `rest` of its lines.
+fn print_group(
+    writer: &mut impl Write,
+    options: &Options,
+    first: &[u8],
+    rest: &[Vec<u8>],
     counter: usize,
-    line: &str,
+    groups_printed: &mut usize,
 ) -> Result<()> {
-    if show_count {
-        out_file.write_fmt(format_args!("{counter:>4} {line}"))?
-    } else {
-        out_file.write_fmt(format_args!("{line}"))?
+    if !selected(options, counter) {
+        return Ok(());
     }
+    match options.all_repeated {
+        Some(delimit) => {
+            if delimit == Delimit::Prepend || (delimit == Delimit::Separate && *groups_printed > 0)
+            {
+                writer.write_all(b"\n")?;
+            }
+            writer.write_all(first)?;
+            for line in rest {
+                writer.write_all(line)?;
+            }
+        }
+        None => {
+            if let Some(width) = options.count {
+                write!(writer, "{counter:>width$} ")?;
+            }
+            writer.write_all(first)?;
+        }
+    }
+    *groups_printed += 1;
     Ok(())
 }
 
-pub fn run(config: Config) -> Result<()> {
-    let mut file =
-        open(&config.in_file).map_err(|e| Error::msg(format!("{}: {}", &config.in_file, e)))?;
-    let mut out_file: Box<dyn Write> = match &config.out_file {
-        Some(out_name) => Box::new(File::create(out_name)?),
-        _ => Box::new(io::stdout()),
-    };
-    let mut line = String::new();
-    let mut prev_line = String::new();
+/// Print the first line seen for every key, in input order, counting every
+/// line in the input that shares its key.
+fn uniq_global(mut reader: impl BufRead, writer: &mut impl Write, options: &Options) -> Result<()> {
+    let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<u8>, usize)> = vec![];
+    let mut line = vec![];
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        if skipped(options, &line) {
+            line.clear();
+            continue;
+        }
+        match seen.get(key(options, &line).as_ref()) {
+            Some(&i) => {
+                groups[i].1 += 1;
+                line.clear();
+            }
+            None => {
+                seen.insert(key(options, &line).into_owned(), groups.len());
+                groups.push((std::mem::take(&mut line), 1));
+            }
+        }
+    }
+    let mut groups_printed = 0;
+    for (line, counter) in groups {
+        print_group(writer, options, &line, &[], counter, &mut groups_printed)?;
+    }
+    Ok(())
+}
+
+/// Filter the lines of `reader` into `writer` as selected by `options`.
+///
+/// Lines are compared as bytes, so input need not be UTF-8. Only the first
+/// line of the current group is kept; its buffer is swapped with the one being
+/// read when a new group starts, so lines are not copied.
+pub fn uniq_stream(
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+    options: &Options,
+) -> Result<()> {
+    if options.global {
+        uniq_global(reader, &mut writer, options)?;
+        return Ok(writer.flush()?);
+    }
+    let mut first = vec![];
+    let mut rest = vec![];
+    let mut line = vec![];
     let mut counter: usize = 0;
+    let mut groups_printed = 0;
     loop {
-        let bytes = file.read_line(&mut line)?;
-        if bytes == 0 {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
             break;
         }
-        if counter > 0 {
-            if line.trim_end() != prev_line.trim_end() {
-                print_format(&mut out_file, config.count, counter, &prev_line)?;
-                counter = 0;
-                prev_line = line.clone();
+        if skipped(options, &line) {
+            continue;
+        }
+        if counter > 0 && key(options, &line) == key(options, &first) {
+            if options.all_repeated.is_some() {
+                rest.push(std::mem::take(&mut line));
             }
         } else {
-            prev_line = line.clone();
+            if counter > 0 {
+                print_group(
+                    &mut writer,
+                    options,
+                    &first,
+                    &rest,
+                    counter,
+                    &mut groups_printed,
+                )?;
+            }
+            std::mem::swap(&mut first, &mut line);
+            rest.clear();
+            counter = 0;
         }
         counter += 1;
-        line.clear();
     }
     if counter > 0 {
-        print_format(&mut out_file, config.count, counter, &prev_line)?;
+        print_group(
+            &mut writer,
+            options,
+            &first,
+            &rest,
+            counter,
+            &mut groups_printed,
+        )?;
+    }
+    Ok(writer.flush()?)
+}
+
+/// Create `filename` for writing. With `no_clobber` an existing file is an
+/// error; otherwise it is truncated.
+fn create(filename: &str, no_clobber: bool) -> Result<File> {
+    if no_clobber {
+        Ok(OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(filename)?)
+    } else {
+        Ok(File::create(filename)?)
+    }
+}
+
+/// Whether `a` and `b` name the same existing file.
+fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+pub fn run(config: Config) -> Result<()> {
+    let out_name = config.out_file.as_deref().filter(|&name| name != "-");
+    if let Some(out_name) = out_name {
+        if config.in_file != "-" && same_file(&config.in_file, out_name) {
+            bail!("{out_name}: input and output are the same file");
+        }
+    }
+    let open_file =
+        |filename: &String| open(filename).map_err(|e| Error::msg(format!("{}: {}", filename, e)));
+    let file = if config.merge.is_empty() {
+        open_file(&config.in_file)?
+    } else {
+        let files = config.merge.iter().map(open_file).collect::<Result<_>>()?;
+        Box::new(MergeReader::new(files)?)
+    };
+    let out_file: Box<dyn Write> = match out_name {
+        Some(out_name) => Box::new(BufWriter::new(
+            create(out_name, config.no_clobber)
+                .map_err(|e| Error::msg(format!("{out_name}: {e}")))?,
+        )),
+        _ => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    uniq_stream(file, out_file, &Options::new(&config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{uniq_stream, Options};
+
+    #[test]
+    fn test_uniq_stream() {
+        let mut out = vec![];
+        let options = Options {
+            count: Some(1),
+            ..Default::default()
+        };
+        uniq_stream(&b"a\xff\na\xff\nb"[..], &mut out, &options).unwrap();
+        assert_eq!(out, b"2 a\xff\n1 b");
     }
-    Ok(())
 }
@@ -0,0 +1,98 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{self, BufRead, Read},
+};
+
+/// Reads the lines of several sorted inputs in merged order, like `sort -m`.
+///
+/// Only the next line of each input is held. Lines are compared as bytes
+/// without their newline, and a last line without a newline gets one so it
+/// cannot run into the next.
+pub struct MergeReader<R> {
+    readers: Vec<R>,
+    heap: BinaryHeap<Reverse<(Vec<u8>, usize)>>,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> MergeReader<R> {
+    pub fn new(readers: Vec<R>) -> io::Result<Self> {
+        let mut merge = Self {
+            readers,
+            heap: BinaryHeap::new(),
+            line: vec![],
+            pos: 0,
+        };
+        for i in 0..merge.readers.len() {
+            merge.refill(i)?;
+        }
+        Ok(merge)
+    }
+
+    /// Queue the next line of input `i`, if it has one, without its newline.
+    fn refill(&mut self, i: usize) -> io::Result<()> {
+        let mut line = vec![];
+        if self.readers[i].read_until(b'\n', &mut line)? > 0 {
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            self.heap.push(Reverse((line, i)));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for MergeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for MergeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.line.len() {
+            if let Some(Reverse((mut line, i))) = self.heap.pop() {
+                line.push(b'\n');
+                self.line = line;
+                self.pos = 0;
+                self.refill(i)?;
+            }
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.line.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergeReader;
+    use std::io::Read;
+
+    #[test]
+    fn test_merge() {
+        let inputs: Vec<&[u8]> = vec![b"a\nc\nc", b"", b"b\nc\nd\n"];
+        let mut merged = String::new();
+        MergeReader::new(inputs)
+            .unwrap()
+            .read_to_string(&mut merged)
+            .unwrap();
+        assert_eq!(merged, "a\nb\nc\nc\nc\nd\n");
+
+        // A line sorts before longer ones it starts, whatever follows
+        let inputs: Vec<&[u8]> = vec![b"a\tb\n", b"a\n"];
+        let mut merged = String::new();
+        MergeReader::new(inputs)
+            .unwrap()
+            .read_to_string(&mut merged)
+            .unwrap();
+        assert_eq!(merged, "a\na\tb\n");
+    }
+}
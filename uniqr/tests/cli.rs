@@ -222,6 +222,17 @@ fn run_stdin_outfile_count(test: &Test) -> Result<()> {
     Ok(())
 }
 
+// --------------------------------------------------
+fn run_args(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn empty() -> Result<()> {
@@ -617,3 +628,316 @@ fn t6_outfile_count() -> Result<()> {
 fn t6_stdin_outfile_count() -> Result<()> {
     run_stdin_outfile_count(&T6)
 }
+
+// --------------------------------------------------
+#[test]
+fn repeated() -> Result<()> {
+    for test in [&EMPTY, &ONE, &THREE, &SKIP, &T1, &T2, &T4, &T5, &T6] {
+        let expected = test.out.replace(".out", ".d.out");
+        run_args(&["-d", test.input], &expected)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn repeated_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--repeated", "-c", THREE.input])
+        .assert()
+        .success()
+        .stdout("   2 a\n   2 b\n   3 c\n   4 d\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn unique() -> Result<()> {
+    for test in [&EMPTY, &ONE, &THREE, &SKIP, &T1, &T2, &T4, &T5, &T6] {
+        let expected = test.out.replace(".out", ".u.out");
+        run_args(&["-u", test.input], &expected)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn repeated_and_unique() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-du", THREE.input])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skip_fields() -> Result<()> {
+    run_args(
+        &["-f", "1", "tests/inputs/fields.txt"],
+        "tests/expected/fields.txt.f1.out",
+    )?;
+    run_args(
+        &["--skip-fields", "2", "tests/inputs/fields.txt"],
+        "tests/expected/fields.txt.f2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn skip_chars() -> Result<()> {
+    run_args(
+        &["-s", "1", "tests/inputs/chars.txt"],
+        "tests/expected/chars.txt.s1.out",
+    )
+}
+
+#[test]
+fn check_chars() -> Result<()> {
+    run_args(
+        &["--check-chars", "2", "tests/inputs/chars.txt"],
+        "tests/expected/chars.txt.w2.out",
+    )
+}
+
+#[test]
+fn skip_and_check_chars_utf8() -> Result<()> {
+    run_args(
+        &["--skip-chars", "1", "-w", "5", "tests/inputs/chars.txt"],
+        "tests/expected/chars.txt.s1w5.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_repeated() -> Result<()> {
+    run_args(
+        &["-D", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D.out",
+    )?;
+    run_args(
+        &["--all-repeated", "tests/inputs/skip.txt"],
+        "tests/expected/skip.txt.D.out",
+    )
+}
+
+#[test]
+fn all_repeated_prepend() -> Result<()> {
+    run_args(
+        &["--all-repeated=prepend", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D-prepend.out",
+    )?;
+    run_args(
+        &["-D=prepend", "tests/inputs/skip.txt"],
+        "tests/expected/skip.txt.D-prepend.out",
+    )
+}
+
+#[test]
+fn all_repeated_separate() -> Result<()> {
+    run_args(
+        &["--all-repeated=separate", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D-separate.out",
+    )?;
+    run_args(
+        &["--all-repeated=separate", "tests/inputs/skip.txt"],
+        "tests/expected/skip.txt.D-separate.out",
+    )
+}
+
+#[test]
+fn all_repeated_with_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-D", "-c", "tests/inputs/three.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn count_width() -> Result<()> {
+    run_args(
+        &["-c", "--count-width", "7", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.c7.out",
+    )?;
+    run_args(
+        &["--count", "--count-width=7", "tests/inputs/skip.txt"],
+        "tests/expected/skip.txt.c7.out",
+    )
+}
+
+#[test]
+fn count_width_requires_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count-width", "7", "tests/inputs/three.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--count"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn global() -> Result<()> {
+    run_args(
+        &["--global", "tests/inputs/global.txt"],
+        "tests/expected/global.txt.g.out",
+    )
+}
+
+#[test]
+fn global_count() -> Result<()> {
+    run_args(
+        &["-g", "-c", "tests/inputs/global.txt"],
+        "tests/expected/global.txt.gc.out",
+    )
+}
+
+#[test]
+fn global_repeated() -> Result<()> {
+    run_args(
+        &["-g", "-d", "tests/inputs/global.txt"],
+        "tests/expected/global.txt.gd.out",
+    )
+}
+
+#[test]
+fn global_unique() -> Result<()> {
+    run_args(
+        &["-g", "-u", "tests/inputs/global.txt"],
+        "tests/expected/global.txt.gu.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn outfile_dash_is_stdout() -> Result<()> {
+    // stdin is not an input when - names OUT_FILE
+    let expected = fs::read_to_string("tests/expected/three.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs/three.txt", "-"])
+        .write_stdin("not read\n")
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn no_clobber() -> Result<()> {
+    let outfile = NamedTempFile::new()?;
+    let outpath = &outfile.path().to_str().unwrap();
+    fs::write(outpath, "keep\n")?;
+
+    Command::cargo_bin(PRG)?
+        .args(["--no-clobber", "tests/inputs/three.txt", outpath])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(format!(
+            "{outpath}: .* [(]os error 17[)]"
+        ))?);
+    assert_eq!(fs::read_to_string(outpath)?, "keep\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs/three.txt", outpath])
+        .assert()
+        .success();
+    let expected = fs::read_to_string("tests/expected/three.txt.out")?;
+    assert_eq!(fs::read_to_string(outpath)?, expected);
+    Ok(())
+}
+
+#[test]
+fn no_clobber_new_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let outpath = dir.path().join("out.txt");
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", "tests/inputs/three.txt", outpath.to_str().unwrap()])
+        .assert()
+        .success();
+    let expected = fs::read_to_string("tests/expected/three.txt.out")?;
+    assert_eq!(fs::read_to_string(outpath)?, expected);
+    Ok(())
+}
+
+#[test]
+fn same_input_and_output() -> Result<()> {
+    let before = fs::read_to_string("tests/inputs/three.txt")?;
+    Command::cargo_bin(PRG)?
+        .args([
+            "tests/inputs/three.txt",
+            "./tests/inputs/../inputs/three.txt",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "input and output are the same file",
+        ));
+    assert_eq!(fs::read_to_string("tests/inputs/three.txt")?, before);
+    Ok(())
+}
+
+// --------------------------------------------------
+const MERGE: [&str; 3] = [
+    "tests/inputs/merge1.txt",
+    "tests/inputs/merge2.txt",
+    "tests/inputs/merge3.txt",
+];
+
+#[test]
+fn merge() -> Result<()> {
+    let mut args = vec!["--merge"];
+    args.extend(MERGE);
+    run_args(&args, "tests/expected/merge.out")
+}
+
+#[test]
+fn merge_count() -> Result<()> {
+    let mut args = vec!["-c", "--count-width", "7", "-m"];
+    args.extend(MERGE);
+    run_args(&args, "tests/expected/merge.c7.out")
+}
+
+#[test]
+fn merge_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args(["--merge", MERGE[0], &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn squeeze_space() -> Result<()> {
+    run_args(
+        &["--squeeze-space", "tests/inputs/loose.txt"],
+        "tests/expected/loose.txt.squeeze.out",
+    )
+}
+
+#[test]
+fn ignore_blank_lines() -> Result<()> {
+    run_args(
+        &["--ignore-blank-lines", "tests/inputs/loose.txt"],
+        "tests/expected/loose.txt.blank.out",
+    )
+}
+
+#[test]
+fn squeeze_space_ignore_blank_lines_count() -> Result<()> {
+    run_args(
+        &[
+            "--squeeze-space",
+            "--ignore-blank-lines",
+            "-c",
+            "tests/inputs/loose.txt",
+        ],
+        "tests/expected/loose.txt.squeeze-blank.c.out",
+    )
+}
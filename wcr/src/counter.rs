@@ -0,0 +1,219 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// A metric accumulated over the lines of one input.
+///
+/// A configured counter serves as a prototype: `fresh` makes the zeroed instance
+/// that counts a single input, so one set of counters is shared by all threads.
+pub trait Counter: Send + Sync {
+    /// Column name used in headers and structured output.
+    fn name(&self) -> &str;
+
+    /// A zeroed counter with the same configuration.
+    fn fresh(&self) -> Box<dyn Counter>;
+
+    /// Feed one line of text, including its terminator.
+    fn update(&mut self, line: &str);
+
+    /// Whether `update` looks at the text. When no counter does, the input is
+    /// read in raw chunks and never split into lines or checked for UTF-8.
+    fn reads_text(&self) -> bool {
+        true
+    }
+
+    /// Called after the last line with the number of bytes read from the input,
+    /// which differs from the length of the text when the input was decoded.
+    fn finish(&mut self, _raw_bytes: usize) {}
+
+    fn value(&self) -> usize;
+
+    /// Fold one input's value into the total row; sums by default.
+    fn combine(&self, total: usize, value: usize) -> usize {
+        total + value
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LineCounter(usize);
+
+impl Counter for LineCounter {
+    fn name(&self) -> &str {
+        "lines"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::default())
+    }
+
+    fn update(&mut self, _line: &str) {
+        self.0 += 1;
+    }
+
+    fn value(&self) -> usize {
+        self.0
+    }
+}
+
+/// Counts whitespace-separated words, or UAX #29 words with `unicode`.
+#[derive(Debug, Default, Clone)]
+pub struct WordCounter {
+    unicode: bool,
+    count: usize,
+}
+
+impl WordCounter {
+    pub fn new(unicode: bool) -> Self {
+        Self { unicode, count: 0 }
+    }
+}
+
+impl Counter for WordCounter {
+    fn name(&self) -> &str {
+        "words"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::new(self.unicode))
+    }
+
+    fn update(&mut self, line: &str) {
+        self.count += if self.unicode {
+            line.unicode_words().count()
+        } else {
+            line.split_whitespace().count()
+        };
+    }
+
+    fn value(&self) -> usize {
+        self.count
+    }
+}
+
+/// Counts bytes as read from the input, before any decoding.
+#[derive(Debug, Default, Clone)]
+pub struct ByteCounter(usize);
+
+impl Counter for ByteCounter {
+    fn name(&self) -> &str {
+        "bytes"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::default())
+    }
+
+    fn update(&mut self, _line: &str) {}
+
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn finish(&mut self, raw_bytes: usize) {
+        self.0 = raw_bytes;
+    }
+
+    fn value(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CharCounter(usize);
+
+impl Counter for CharCounter {
+    fn name(&self) -> &str {
+        "chars"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::default())
+    }
+
+    fn update(&mut self, line: &str) {
+        self.0 += line.chars().count();
+    }
+
+    fn value(&self) -> usize {
+        self.0
+    }
+}
+
+/// Counts extended grapheme clusters.
+#[derive(Debug, Default, Clone)]
+pub struct GraphemeCounter(usize);
+
+impl Counter for GraphemeCounter {
+    fn name(&self) -> &str {
+        "graphemes"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::default())
+    }
+
+    fn update(&mut self, line: &str) {
+        self.0 += line.graphemes(true).count();
+    }
+
+    fn value(&self) -> usize {
+        self.0
+    }
+}
+
+/// Tracks the widest line; the total is the widest line of all inputs.
+#[derive(Debug, Default, Clone)]
+pub struct MaxLineLengthCounter(usize);
+
+impl Counter for MaxLineLengthCounter {
+    fn name(&self) -> &str {
+        "max_line_length"
+    }
+
+    fn fresh(&self) -> Box<dyn Counter> {
+        Box::new(Self::default())
+    }
+
+    fn update(&mut self, line: &str) {
+        self.0 = self.0.max(line_width(line));
+    }
+
+    fn value(&self) -> usize {
+        self.0
+    }
+
+    fn combine(&self, total: usize, value: usize) -> usize {
+        total.max(value)
+    }
+}
+
+/// Display width of a line like GNU wc: tabs advance to the next multiple of 8,
+/// carriage returns and form feeds return to the start of the line.
+fn line_width(line: &str) -> usize {
+    let mut max_width = 0;
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            '\n' => {}
+            '\r' | '\x0c' => width = 0,
+            '\t' => width = (width / 8 + 1) * 8,
+            c => width += c.width().unwrap_or(0),
+        }
+        max_width = max_width.max(width);
+    }
+    max_width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line_width;
+
+    #[test]
+    fn test_line_width() {
+        assert_eq!(line_width(""), 0);
+        assert_eq!(line_width("hello\n"), 5);
+        assert_eq!(line_width("a\tb"), 9);
+        assert_eq!(line_width("\t\t"), 16);
+        assert_eq!(line_width("日本語"), 6);
+        assert_eq!(line_width("longer\rab"), 6);
+    }
+}
@@ -0,0 +1,121 @@
+use encoding_rs::{Decoder, Encoding};
+use std::io::{self, Read};
+
+const CHUNK_SIZE: usize = 8192;
+
+/// Transcodes a byte stream in some encoding to UTF-8 while counting the raw bytes read.
+pub struct DecodeReader<R> {
+    inner: R,
+    encoding: &'static Encoding,
+    decoder: Decoder,
+    strict: bool,
+    decoded: String,
+    pos: usize,
+    raw_bytes: usize,
+    done: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// With `strict`, invalid byte sequences are reported as errors instead of
+    /// being replaced with U+FFFD.
+    pub fn new(inner: R, encoding: &'static Encoding, strict: bool) -> Self {
+        Self {
+            inner,
+            encoding,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            strict,
+            decoded: String::new(),
+            pos: 0,
+            raw_bytes: 0,
+            done: false,
+        }
+    }
+
+    /// Number of undecoded bytes read from the inner reader so far.
+    pub fn raw_bytes(&self) -> usize {
+        self.raw_bytes
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut raw = [0; CHUNK_SIZE];
+        let read = self.inner.read(&mut raw)?;
+        self.raw_bytes += read;
+        let last = read == 0;
+
+        self.decoded.clear();
+        self.pos = 0;
+        let needed = self
+            .decoder
+            .max_utf8_buffer_length(read)
+            .unwrap_or(read * 3 + 16);
+        self.decoded.reserve(needed);
+        let (_, _, had_errors) =
+            self.decoder
+                .decode_to_string(&raw[..read], &mut self.decoded, last);
+        if had_errors && self.strict {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {} byte sequence", self.encoding.name()),
+            ));
+        }
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.decoded.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let available = &self.decoded.as_bytes()[self.pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecodeReader;
+    use std::io::Read;
+
+    fn decode(
+        bytes: &[u8],
+        encoding: &'static encoding_rs::Encoding,
+        strict: bool,
+    ) -> (std::io::Result<String>, usize) {
+        let mut reader = DecodeReader::new(bytes, encoding, strict);
+        let mut decoded = String::new();
+        let result = reader.read_to_string(&mut decoded).map(|_| decoded);
+        (result, reader.raw_bytes())
+    }
+
+    #[test]
+    fn test_decode() {
+        let (decoded, raw) = decode(b"h\x00\xe9\x00\n\x00", encoding_rs::UTF_16LE, false);
+        assert_eq!(decoded.unwrap(), "hé\n");
+        assert_eq!(raw, 6);
+
+        let (decoded, raw) = decode(b"\x00h\x00\xe9\x00\n", encoding_rs::UTF_16BE, false);
+        assert_eq!(decoded.unwrap(), "hé\n");
+        assert_eq!(raw, 6);
+
+        let (decoded, _) = decode(b"caf\xe9", encoding_rs::WINDOWS_1252, true);
+        assert_eq!(decoded.unwrap(), "café");
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let (decoded, raw) = decode(b"a\xffb", encoding_rs::UTF_8, false);
+        assert_eq!(decoded.unwrap(), "a\u{fffd}b");
+        assert_eq!(raw, 3);
+
+        let (decoded, _) = decode(b"a\xffb", encoding_rs::UTF_8, true);
+        assert!(decoded.is_err());
+    }
+}
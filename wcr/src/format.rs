@@ -0,0 +1,160 @@
+use crate::FileInfo;
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::io::{self, Stdout};
+
+/// Output stage receiving one row per file and the total row.
+pub trait Formatter {
+    fn file(&mut self, name: Option<&str>, file_info: &FileInfo) -> Result<()>;
+
+    /// `labeled` is false when only the total is printed.
+    fn total(&mut self, labeled: bool, file_info: &FileInfo) -> Result<()>;
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Whitespace-aligned columns like wc.
+pub struct PlainFormatter {
+    columns: Vec<String>,
+    width: usize,
+}
+
+impl PlainFormatter {
+    pub fn new(columns: Vec<String>, width: usize) -> Self {
+        Self { columns, width }
+    }
+
+    fn row(&self, name: Option<&str>, file_info: &FileInfo) {
+        let counts = file_info.counts()[..self.columns.len()]
+            .iter()
+            .map(|value| format!("{value:>width$}", width = self.width))
+            .collect::<Vec<_>>()
+            .join(" ");
+        match name {
+            Some(name) => println!("{counts} {name}"),
+            None => println!("{counts}"),
+        }
+    }
+}
+
+impl Formatter for PlainFormatter {
+    fn file(&mut self, name: Option<&str>, file_info: &FileInfo) -> Result<()> {
+        self.row(name, file_info);
+        Ok(())
+    }
+
+    fn total(&mut self, labeled: bool, file_info: &FileInfo) -> Result<()> {
+        self.row(labeled.then_some("total"), file_info);
+        Ok(())
+    }
+}
+
+/// A single JSON document with a `files` array and an optional `total` object.
+pub struct JsonFormatter {
+    columns: Vec<String>,
+    files: Vec<Value>,
+    total: Option<Value>,
+}
+
+impl JsonFormatter {
+    pub fn new(columns: Vec<String>) -> Self {
+        Self {
+            columns,
+            files: vec![],
+            total: None,
+        }
+    }
+
+    fn record(&self, file_info: &FileInfo) -> Map<String, Value> {
+        self.columns
+            .iter()
+            .zip(file_info.counts())
+            .map(|(column, value)| (column.clone(), json!(value)))
+            .collect()
+    }
+}
+
+impl Formatter for JsonFormatter {
+    fn file(&mut self, name: Option<&str>, file_info: &FileInfo) -> Result<()> {
+        let mut record = Map::new();
+        record.insert("name".to_string(), json!(name));
+        record.extend(self.record(file_info));
+        self.files.push(Value::Object(record));
+        Ok(())
+    }
+
+    fn total(&mut self, _labeled: bool, file_info: &FileInfo) -> Result<()> {
+        self.total = Some(Value::Object(self.record(file_info)));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut document = Map::new();
+        document.insert("files".to_string(), Value::Array(self.files.split_off(0)));
+        if let Some(total) = self.total.take() {
+            document.insert("total".to_string(), total);
+        }
+        println!("{}", Value::Object(document));
+        Ok(())
+    }
+}
+
+/// Delimited records with a header row; stdin is named `-` and the total row `total`.
+pub struct DelimitedFormatter {
+    columns: Vec<String>,
+    writer: csv::Writer<Stdout>,
+    wrote_header: bool,
+}
+
+impl DelimitedFormatter {
+    pub fn new(columns: Vec<String>, delimiter: u8) -> Self {
+        let writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(io::stdout());
+        Self {
+            columns,
+            writer,
+            wrote_header: false,
+        }
+    }
+
+    fn header(&mut self) -> Result<()> {
+        if !self.wrote_header {
+            let mut header = vec!["name"];
+            header.extend(self.columns.iter().map(String::as_str));
+            self.writer.write_record(header)?;
+            self.wrote_header = true;
+        }
+        Ok(())
+    }
+
+    fn row(&mut self, name: &str, file_info: &FileInfo) -> Result<()> {
+        self.header()?;
+        let mut record = vec![name.to_string()];
+        record.extend(
+            file_info.counts()[..self.columns.len()]
+                .iter()
+                .map(|value| value.to_string()),
+        );
+        self.writer.write_record(record)?;
+        Ok(())
+    }
+}
+
+impl Formatter for DelimitedFormatter {
+    fn file(&mut self, name: Option<&str>, file_info: &FileInfo) -> Result<()> {
+        self.row(name.unwrap_or("-"), file_info)
+    }
+
+    fn total(&mut self, _labeled: bool, file_info: &FileInfo) -> Result<()> {
+        self.row("total", file_info)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.header()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
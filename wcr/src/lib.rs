@@ -1,10 +1,83 @@
-use anyhow::Result;
-use clap::Parser;
+mod counter;
+mod decode;
+mod format;
+
+pub use counter::{
+    ByteCounter, CharCounter, Counter, GraphemeCounter, LineCounter, MaxLineLengthCounter,
+    WordCounter,
+};
+
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, ValueEnum};
+use decode::DecodeReader;
+use encoding_rs::Encoding;
+use format::{DelimitedFormatter, Formatter, JsonFormatter, PlainFormatter};
+use glob::Pattern;
+use rayon::prelude::*;
 use std::{
     fmt::Debug,
-    fs::File,
-    io::{self, BufRead, BufReader},
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    num::NonZeroUsize,
 };
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TotalWhen {
+    /// Print a total when more than one file is given
+    Auto,
+    /// Always print a total
+    Always,
+    /// Print only the total, without a label
+    Only,
+    /// Never print a total
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns
+    Plain,
+    /// A JSON document with per-file records and the total
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputEncoding {
+    Utf8,
+    Utf16le,
+    Utf16be,
+    Latin1,
+}
+
+impl InputEncoding {
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            InputEncoding::Utf8 => encoding_rs::UTF_8,
+            InputEncoding::Utf16le => encoding_rs::UTF_16LE,
+            InputEncoding::Utf16be => encoding_rs::UTF_16BE,
+            // encoding_rs follows WHATWG, where latin1 is windows-1252; every byte
+            // still decodes to exactly one character
+            InputEncoding::Latin1 => encoding_rs::WINDOWS_1252,
+        }
+    }
+}
+
+/// What to do with a byte order mark at the start of a file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BomPolicy {
+    /// Count the BOM like any other bytes and characters
+    #[default]
+    Count,
+    /// Leave the BOM out of all counts; a UTF-16 BOM also selects the input encoding
+    Skip,
+    /// Report files starting with a BOM as errors
+    Error,
+}
 
 #[derive(Parser, Debug)]
 #[command(
@@ -33,31 +106,107 @@ pub struct Config {
     /// Show character count
     #[arg(short = 'm', long = "chars")]
     chars: bool,
+
+    /// Show extended grapheme cluster count
+    #[arg(short = 'g', long = "graphemes")]
+    graphemes: bool,
+
+    /// Count words using Unicode word boundaries (UAX #29) instead of whitespace
+    #[arg(long = "unicode-words")]
+    unicode_words: bool,
+
+    /// Decode input from ENCODING; invalid sequences are replaced with U+FFFD
+    #[arg(long = "encoding", value_name = "ENCODING", value_enum)]
+    encoding: Option<InputEncoding>,
+
+    /// With --encoding, report invalid byte sequences as errors
+    #[arg(long = "strict-encoding", requires = "encoding")]
+    strict_encoding: bool,
+
+    /// How to handle a UTF-8 or UTF-16 byte order mark
+    #[arg(long = "bom", value_name = "POLICY", value_enum, default_value_t = BomPolicy::Count)]
+    bom: BomPolicy,
+
+    /// Show maximum display width of a line
+    #[arg(short = 'L', long = "max-line-length")]
+    max_line_length: bool,
+
+    /// Count every file below directory arguments, skipping binary files
+    #[arg(short = 'r', long = "recursive")]
+    recursive: bool,
+
+    /// Expand FILE arguments as glob patterns, e.g. 'src/**/*.rs'
+    #[arg(long = "glob")]
+    glob: bool,
+
+    /// With --recursive, only count files whose name matches the glob PATTERN
+    #[arg(long = "include", value_name = "PATTERN", requires = "recursive")]
+    includes: Vec<Pattern>,
+
+    /// Exit with an error if any file has more than N lines
+    #[arg(long = "fail-if-lines-over", value_name = "N")]
+    max_lines: Option<usize>,
+
+    /// Exit with an error if any file has fewer than N lines
+    #[arg(long = "fail-if-lines-under", value_name = "N")]
+    min_lines: Option<usize>,
+
+    /// Exit with an error if any file has more than N bytes
+    #[arg(long = "fail-if-bytes-over", value_name = "N")]
+    max_bytes: Option<usize>,
+
+    /// Exit with an error if any file has fewer than N bytes
+    #[arg(long = "fail-if-bytes-under", value_name = "N")]
+    min_bytes: Option<usize>,
+
+    /// Label for counts read from stdin
+    #[arg(long = "stdin-name", value_name = "NAME")]
+    stdin_name: Option<String>,
+
+    /// Number of threads used to count files [default: number of CPUs]
+    #[arg(long = "threads", value_name = "N")]
+    threads: Option<NonZeroUsize>,
+
+    /// When to print a line with total counts
+    #[arg(
+        long = "total",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = TotalWhen::Auto
+    )]
+    total: TotalWhen,
+
+    /// Output format
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = OutputFormat::Plain
+    )]
+    format: OutputFormat,
 }
 
+/// Values of each counter for one input, in the order the counters were given.
 #[derive(Debug, PartialEq)]
 pub struct FileInfo {
-    num_lines: usize,
-    num_words: usize,
-    num_bytes: usize,
-    num_chars: usize,
+    counts: Vec<usize>,
 }
 
 impl FileInfo {
-    fn new() -> FileInfo {
+    fn new(num_counters: usize) -> FileInfo {
         FileInfo {
-            num_lines: 0,
-            num_words: 0,
-            num_bytes: 0,
-            num_chars: 0,
+            counts: vec![0; num_counters],
         }
     }
 
-    fn add(&mut self, orig: &FileInfo) {
-        self.num_lines += orig.num_lines;
-        self.num_words += orig.num_words;
-        self.num_bytes += orig.num_bytes;
-        self.num_chars += orig.num_chars;
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    fn add(&mut self, orig: &FileInfo, counters: &[Box<dyn Counter>]) {
+        for ((total, value), counter) in self.counts.iter_mut().zip(&orig.counts).zip(counters) {
+            *total = counter.combine(*total, *value);
+        }
     }
 }
 
@@ -65,9 +214,16 @@ pub fn get_args() -> Result<Config> {
     let args = Config::try_parse();
     match args {
         Ok(mut args) => {
-            let no_flags = [args.lines, args.words, args.bytes, args.chars]
-                .iter()
-                .all(|v| v == &false);
+            let no_flags = [
+                args.lines,
+                args.words,
+                args.bytes,
+                args.chars,
+                args.graphemes,
+                args.max_line_length,
+            ]
+            .iter()
+            .all(|v| v == &false);
             if no_flags {
                 args = Config {
                     lines: true,
@@ -89,91 +245,479 @@ fn open(filename: &str) -> Result<Box<dyn BufRead>> {
     }
 }
 
-pub fn count(mut file: impl BufRead) -> Result<FileInfo> {
-    let mut num_lines = 0;
-    let mut num_words = 0;
+/// Options controlling how counts are computed.
+#[derive(Debug, Default, Clone)]
+pub struct CountOptions {
+    /// Decode input from this encoding instead of requiring UTF-8.
+    pub encoding: Option<&'static Encoding>,
+    /// Fail on invalid byte sequences rather than replacing them.
+    pub strict_encoding: bool,
+    /// Handling of a byte order mark at the start of the input.
+    pub bom: BomPolicy,
+}
+
+impl CountOptions {
+    fn new(config: &Config) -> Self {
+        Self {
+            encoding: config.encoding.map(InputEncoding::encoding),
+            strict_encoding: config.strict_encoding,
+            bom: config.bom,
+        }
+    }
+}
+
+/// Count an input with fresh instances of `counters`. A byte order mark is
+/// handled according to the options, and bytes are always counted as read.
+pub fn count(
+    mut file: impl BufRead,
+    options: &CountOptions,
+    counters: &[Box<dyn Counter>],
+) -> Result<FileInfo> {
+    let mut encoding = options.encoding;
+    if let Some((bom_encoding, bom_len)) = Encoding::for_bom(file.fill_buf()?) {
+        match options.bom {
+            BomPolicy::Count => {}
+            BomPolicy::Skip => {
+                file.consume(bom_len);
+                if encoding.is_none() && bom_encoding != encoding_rs::UTF_8 {
+                    encoding = Some(bom_encoding);
+                }
+            }
+            BomPolicy::Error => bail!("{} byte order mark found", bom_encoding.name()),
+        }
+    }
+
+    let mut counters: Vec<_> = counters.iter().map(|counter| counter.fresh()).collect();
+    let raw_bytes = match encoding {
+        None if !counters.iter().any(|counter| counter.reads_text()) => count_bytes(file)?,
+        None => count_lines(file, &mut counters)?,
+        Some(encoding) => {
+            let mut reader =
+                BufReader::new(DecodeReader::new(file, encoding, options.strict_encoding));
+            count_lines(&mut reader, &mut counters)?;
+            reader.get_ref().raw_bytes()
+        }
+    };
+    for counter in &mut counters {
+        counter.finish(raw_bytes);
+    }
+    Ok(FileInfo {
+        counts: counters.iter().map(|counter| counter.value()).collect(),
+    })
+}
+
+/// Read the input in raw chunks, returning the number of bytes read.
+fn count_bytes(mut file: impl BufRead) -> Result<usize> {
     let mut num_bytes = 0;
-    let mut num_chars = 0;
+    loop {
+        let len = file.fill_buf()?.len();
+        if len == 0 {
+            break;
+        }
+        num_bytes += len;
+        file.consume(len);
+    }
+    Ok(num_bytes)
+}
 
+/// Feed every line to the counters, returning the number of bytes read.
+fn count_lines(mut file: impl BufRead, counters: &mut [Box<dyn Counter>]) -> Result<usize> {
+    let mut num_bytes = 0;
     let mut line = String::new();
     loop {
-        let read_byes = file.read_line(&mut line)?;
-        if read_byes == 0 {
+        let read_bytes = file.read_line(&mut line)?;
+        if read_bytes == 0 {
             break;
         }
-        num_lines += 1;
-        num_words += line.split_whitespace().count();
-        num_bytes += read_byes;
-        num_chars += line.chars().count();
+        num_bytes += read_bytes;
+        for counter in counters.iter_mut() {
+            counter.update(&line);
+        }
         line.clear();
     }
+    Ok(num_bytes)
+}
 
-    Ok(FileInfo {
-        num_lines,
-        num_words,
-        num_bytes,
-        num_chars,
-    })
+/// The counters requested on the command line, in wc's column order.
+fn counters(config: &Config) -> Vec<Box<dyn Counter>> {
+    let counters: [(bool, Box<dyn Counter>); 6] = [
+        (config.lines, Box::new(LineCounter::default())),
+        (
+            config.words,
+            Box::new(WordCounter::new(config.unicode_words)),
+        ),
+        (config.bytes, Box::new(ByteCounter::default())),
+        (config.chars, Box::new(CharCounter::default())),
+        (config.graphemes, Box::new(GraphemeCounter::default())),
+        (
+            config.max_line_length,
+            Box::new(MaxLineLengthCounter::default()),
+        ),
+    ];
+    counters
+        .into_iter()
+        .filter_map(|(show, counter)| show.then_some(counter))
+        .collect()
 }
 
-fn format_count(count: usize, show: bool) -> String {
-    if show {
-        format!("{count:>8}")
-    } else {
-        "".to_string()
-    }
+/// Where the line and byte counts the thresholds check are among the counters.
+#[derive(Debug, PartialEq)]
+struct ThresholdColumns {
+    lines: Option<usize>,
+    bytes: Option<usize>,
 }
 
-fn print_file_info(config: &Config, filename: &str, file_info: &FileInfo) {
-    let show_file_name = if filename != "-" {
-        format!(" {filename}")
-    } else {
-        "".to_string()
+/// Find the built-in line and byte counters by their place in the column order
+/// of `counters`, and add the ones the thresholds need that are not shown to
+/// the end of `all`; they are counted there and left out of the output.
+fn threshold_columns(config: &Config, all: &mut Vec<Box<dyn Counter>>) -> ThresholdColumns {
+    let mut columns = ThresholdColumns {
+        lines: config.lines.then_some(0),
+        bytes: config
+            .bytes
+            .then_some(usize::from(config.lines) + usize::from(config.words)),
     };
-    println!(
-        "{}{}{}{}{}",
-        format_count(file_info.num_lines, config.lines),
-        format_count(file_info.num_words, config.words),
-        format_count(file_info.num_bytes, config.bytes),
-        format_count(file_info.num_chars, config.chars),
-        show_file_name
-    );
+    if (config.max_lines.is_some() || config.min_lines.is_some()) && columns.lines.is_none() {
+        columns.lines = Some(all.len());
+        all.push(Box::new(LineCounter::default()));
+    }
+    if (config.max_bytes.is_some() || config.min_bytes.is_some()) && columns.bytes.is_none() {
+        columns.bytes = Some(all.len());
+        all.push(Box::new(ByteCounter::default()));
+    }
+    columns
+}
+
+/// Column width like GNU wc: enough digits for the combined size of all regular
+/// files, at least 7 when stdin or another non-regular file is read, and no
+/// padding for a single count of a single file (or of the total alone).
+fn number_width(config: &Config, files: &[String], num_columns: usize) -> usize {
+    if num_columns == 1 && (files.len() == 1 || config.total == TotalWhen::Only) {
+        return 1;
+    }
+    let mut minimum_width = 1;
+    let mut total_size: u64 = 0;
+    for filename in files {
+        match fs::metadata(filename) {
+            Ok(metadata) if filename != "-" && metadata.is_file() => total_size += metadata.len(),
+            Ok(_) => minimum_width = 7,
+            Err(_) if filename == "-" => minimum_width = 7,
+            Err(_) => {}
+        }
+    }
+    total_size.to_string().len().max(minimum_width)
+}
+
+fn formatter(config: &Config, files: &[String], columns: Vec<String>) -> Box<dyn Formatter> {
+    match config.format {
+        OutputFormat::Plain => {
+            let width = number_width(config, files, columns.len());
+            Box::new(PlainFormatter::new(columns, width))
+        }
+        OutputFormat::Json => Box::new(JsonFormatter::new(columns)),
+        OutputFormat::Csv => Box::new(DelimitedFormatter::new(columns, b',')),
+        OutputFormat::Tsv => Box::new(DelimitedFormatter::new(columns, b'\t')),
+    }
+}
+
+/// Describe every threshold given on the command line that `file_info` violates.
+fn threshold_violations(
+    config: &Config,
+    filename: &str,
+    file_info: &FileInfo,
+    columns: &ThresholdColumns,
+) -> Vec<String> {
+    let checks = [
+        (config.max_lines, columns.lines, "lines", true),
+        (config.min_lines, columns.lines, "lines", false),
+        (config.max_bytes, columns.bytes, "bytes", true),
+        (config.min_bytes, columns.bytes, "bytes", false),
+    ];
+    checks
+        .into_iter()
+        .filter_map(|(limit, column, unit, over)| {
+            let limit = limit?;
+            let value = file_info.counts[column?];
+            if over && value > limit {
+                Some(format!(
+                    "{filename}: {value} {unit} is over the limit of {limit}"
+                ))
+            } else if !over && value < limit {
+                Some(format!(
+                    "{filename}: {value} {unit} is under the limit of {limit}"
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Guess whether a file is binary the way git does: a NUL in the first 8000 bytes.
+fn is_binary(path: &std::path::Path) -> io::Result<bool> {
+    let mut buf = Vec::with_capacity(8000);
+    File::open(path)?.take(8000).read_to_end(&mut buf)?;
+    Ok(buf.contains(&0))
+}
+
+/// Expand FILE arguments as glob patterns when `--glob` is given, in the order
+/// the patterns were given. A pattern that matches nothing is an error.
+fn expand_globs(config: &Config) -> Result<Vec<String>> {
+    if !config.glob {
+        return Ok(config.files.clone());
+    }
+    let mut files = vec![];
+    for pattern in &config.files {
+        if pattern == "-" {
+            files.push(pattern.clone());
+            continue;
+        }
+        let num_files = files.len();
+        for path in glob::glob(pattern).map_err(|e| anyhow!("{pattern}: {e}"))? {
+            match path {
+                Ok(path) => files.push(path.to_string_lossy().into_owned()),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        if files.len() == num_files {
+            bail!("{pattern}: no files match");
+        }
+    }
+    Ok(files)
+}
+
+/// Expand directory arguments into the files below them when `--recursive` is given.
+fn expand_files(config: &Config, args: Vec<String>) -> Vec<String> {
+    if !config.recursive {
+        return args;
+    }
+    let mut files = vec![];
+    for filename in args {
+        if filename == "-" || !fs::metadata(&filename).is_ok_and(|m| m.is_dir()) {
+            files.push(filename);
+            continue;
+        }
+        for entry in WalkDir::new(&filename).sort_by_file_name() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("{e}");
+                    continue;
+                }
+            };
+            let name = entry.file_name().to_string_lossy();
+            if !entry.file_type().is_file()
+                || !(config.includes.is_empty()
+                    || config.includes.iter().any(|pattern| pattern.matches(&name)))
+            {
+                continue;
+            }
+            match is_binary(entry.path()) {
+                Ok(true) => {}
+                Ok(false) => files.push(entry.path().to_string_lossy().into_owned()),
+                Err(e) => eprintln!("{}: {e}", entry.path().display()),
+            }
+        }
+    }
+    files
 }
 
 pub fn run(config: Config) -> Result<()> {
-    let mut total_file_info = FileInfo::new();
-    for filename in &config.files {
-        match open(filename) {
+    run_with(config, vec![])
+}
+
+/// Like `run`, with `custom` counters shown after the built-in ones.
+pub fn run_with(config: Config, custom: Vec<Box<dyn Counter>>) -> Result<()> {
+    let mut counters = counters(&config);
+    counters.extend(custom);
+    let columns = counters
+        .iter()
+        .map(|counter| counter.name().to_string())
+        .collect();
+    let thresholds = threshold_columns(&config, &mut counters);
+
+    let files = expand_files(&config, expand_globs(&config)?);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.threads.map_or(0, NonZeroUsize::get))
+        .build()?;
+    let options = CountOptions::new(&config);
+    let file_infos: Vec<Result<FileInfo>> = pool.install(|| {
+        files
+            .par_iter()
+            .map(|filename| count(open(filename)?, &options, &counters))
+            .collect()
+    });
+
+    let mut formatter = formatter(&config, &files, columns);
+    let mut total_file_info = FileInfo::new(counters.len());
+    let mut violations = vec![];
+    for (filename, file_info) in files.iter().zip(file_infos) {
+        match file_info {
             Err(e) => eprintln!("{filename}: {e}"),
-            Ok(file) => {
-                let file_info = count(file)?;
-                print_file_info(&config, filename, &file_info);
-                total_file_info.add(&file_info);
+            Ok(file_info) => {
+                violations.extend(threshold_violations(
+                    &config,
+                    filename,
+                    &file_info,
+                    &thresholds,
+                ));
+                if config.total != TotalWhen::Only {
+                    let name = match filename.as_str() {
+                        "-" => config.stdin_name.as_deref(),
+                        filename => Some(filename),
+                    };
+                    formatter.file(name, &file_info)?;
+                }
+                total_file_info.add(&file_info, &counters);
             }
         }
     }
-    if config.files.len() > 1 {
-        print_file_info(&config, "total", &total_file_info);
+    match config.total {
+        TotalWhen::Auto if files.len() > 1 => formatter.total(true, &total_file_info)?,
+        TotalWhen::Always => formatter.total(true, &total_file_info)?,
+        TotalWhen::Only => formatter.total(false, &total_file_info)?,
+        _ => {}
+    }
+    formatter.finish()?;
+    if !violations.is_empty() {
+        bail!(violations.join("\n"));
     }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{count, FileInfo};
+    use super::{
+        count, counters, threshold_columns, threshold_violations, ByteCounter, CharCounter, Config,
+        CountOptions, Counter, FileInfo, GraphemeCounter, LineCounter, MaxLineLengthCounter,
+        ThresholdColumns, WordCounter,
+    };
+    use clap::Parser;
     use std::io::Cursor;
 
+    fn all_counters() -> Vec<Box<dyn Counter>> {
+        vec![
+            Box::new(LineCounter::default()),
+            Box::new(WordCounter::new(false)),
+            Box::new(ByteCounter::default()),
+            Box::new(CharCounter::default()),
+            Box::new(GraphemeCounter::default()),
+            Box::new(MaxLineLengthCounter::default()),
+        ]
+    }
+
     #[test]
     fn test_count() {
         let text = "I don't want the world. I just want your half.\r\n";
-        let info = count(Cursor::new(text));
+        let info = count(Cursor::new(text), &CountOptions::default(), &all_counters());
         assert!(info.is_ok());
-        let expected = FileInfo {
-            num_lines: 1,
-            num_words: 10,
-            num_chars: 48,
-            num_bytes: 48,
+        assert_eq!(info.unwrap().counts(), [1, 10, 48, 48, 47, 46]);
+    }
+
+    #[test]
+    fn test_count_bytes_only() {
+        // Only bytes are counted, so the text need not be UTF-8
+        let text: &[u8] = b"a\xff\nb";
+        let counters: Vec<Box<dyn Counter>> = vec![Box::new(ByteCounter::default())];
+        let info = count(Cursor::new(text), &CountOptions::default(), &counters).unwrap();
+        assert_eq!(info.counts(), [4]);
+        let counters: Vec<Box<dyn Counter>> = vec![Box::new(CharCounter::default())];
+        assert!(count(Cursor::new(text), &CountOptions::default(), &counters).is_err());
+    }
+
+    #[test]
+    fn test_count_unicode_words() {
+        let text = "Hello,world! 日本語のテキスト\n";
+        let counters: Vec<Box<dyn Counter>> = vec![Box::new(WordCounter::new(true))];
+        let info = count(Cursor::new(text), &CountOptions::default(), &counters).unwrap();
+        assert_eq!(info.counts(), [7]);
+        let counters: Vec<Box<dyn Counter>> = vec![Box::new(WordCounter::new(false))];
+        let info = count(Cursor::new(text), &CountOptions::default(), &counters).unwrap();
+        assert_eq!(info.counts(), [2]);
+    }
+
+    #[test]
+    fn test_count_graphemes() {
+        let text = "e\u{301}👨‍👩‍👧 🇯🇵\n";
+        let counters: Vec<Box<dyn Counter>> = vec![
+            Box::new(CharCounter::default()),
+            Box::new(GraphemeCounter::default()),
+        ];
+        let info = count(Cursor::new(text), &CountOptions::default(), &counters).unwrap();
+        assert_eq!(info.counts(), [11, 5]);
+    }
+
+    #[derive(Default)]
+    struct SentenceCounter(usize);
+
+    impl Counter for SentenceCounter {
+        fn name(&self) -> &str {
+            "sentences"
+        }
+
+        fn fresh(&self) -> Box<dyn Counter> {
+            Box::new(Self::default())
+        }
+
+        fn update(&mut self, line: &str) {
+            self.0 += line.matches(['.', '!', '?']).count();
+        }
+
+        fn value(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_count_custom() {
+        let text = "One. Two!\nThree?\n";
+        let counters: Vec<Box<dyn Counter>> = vec![
+            Box::new(LineCounter::default()),
+            Box::new(SentenceCounter::default()),
+        ];
+        let info = count(Cursor::new(text), &CountOptions::default(), &counters).unwrap();
+        assert_eq!(info.counts(), [2, 3]);
+    }
+
+    struct LinesNamedCounter;
+
+    impl Counter for LinesNamedCounter {
+        fn name(&self) -> &str {
+            "lines"
+        }
+
+        fn fresh(&self) -> Box<dyn Counter> {
+            Box::new(Self)
+        }
+
+        fn update(&mut self, _line: &str) {}
+
+        fn value(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn test_threshold_columns() {
+        let config = Config::try_parse_from(["wcr", "-wc", "--fail-if-lines-over", "1"]).unwrap();
+        let mut all = counters(&config);
+        // A custom counter named like a built-in one is not checked
+        all.push(Box::new(LinesNamedCounter));
+        let columns = threshold_columns(&config, &mut all);
+        assert_eq!(
+            columns,
+            ThresholdColumns {
+                lines: Some(3),
+                bytes: Some(1)
+            }
+        );
+        assert_eq!(all[3].name(), "lines");
+        let file_info = FileInfo {
+            counts: vec![2, 10, 0, 3],
         };
-        assert_eq!(info.unwrap(), expected);
+        assert_eq!(
+            threshold_violations(&config, "a.txt", &file_info, &columns),
+            ["a.txt: 3 lines is over the limit of 1"]
+        );
     }
 }
@@ -9,6 +9,8 @@ const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const UNICODE: &str = "tests/inputs/unicode.txt";
+const GRAPHEMES: &str = "tests/inputs/graphemes.txt";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -218,3 +220,443 @@ fn test_all_words_lines() -> Result<()> {
 fn test_all_bytes_lines() -> Result<()> {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn fox_max_line_length() -> Result<()> {
+    run(&["-L", FOX], "tests/expected/fox.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_max_line_length() -> Result<()> {
+    run(&["-L", EMPTY, FOX, ATLAMAL], "tests/expected/all.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_lines_max_line_length() -> Result<()> {
+    run(
+        &["--lines", "--max-line-length", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.lL.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_threads() -> Result<()> {
+    run(
+        &["--threads", "1", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.out",
+    )?;
+    run(
+        &["--threads", "3", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_zero_threads() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--threads", "0", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--threads <N>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_total_always() -> Result<()> {
+    run(
+        &["--total", "always", FOX],
+        "tests/expected/fox.txt.total_always.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_total_auto() -> Result<()> {
+    run(
+        &["--total", "auto", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_total_only() -> Result<()> {
+    run(
+        &["--total=only", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.total_only.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_total_never() -> Result<()> {
+    run(
+        &["--total=never", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.total_never.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_atlamal_lines_json() -> Result<()> {
+    run(
+        &["--format", "json", "-l", FOX, ATLAMAL],
+        "tests/expected/fox_atlamal.l.json.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_csv() -> Result<()> {
+    run(
+        &["--format", "csv", EMPTY, FOX, ATLAMAL],
+        "tests/expected/all.csv.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_atlamal_words_max_line_length_tsv() -> Result<()> {
+    run(
+        &["--format", "tsv", "-wL", "--total", "never", FOX, ATLAMAL],
+        "tests/expected/fox_atlamal.wL.tsv.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_stdin_lines() -> Result<()> {
+    let input = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .arg("-l")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("1\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_stdin_total_always() -> Result<()> {
+    let input = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .args(["-l", "--total", "always", "-", FOX])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("      1\n      1 tests/inputs/fox.txt\n      2 total\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode() -> Result<()> {
+    run(&[UNICODE], "tests/expected/unicode.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode_unicode_words() -> Result<()> {
+    run(
+        &["--unicode-words", UNICODE],
+        "tests/expected/unicode.txt.unicode_words.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_atlamal_words_unicode_words() -> Result<()> {
+    run(
+        &["-w", "--unicode-words", FOX, ATLAMAL],
+        "tests/expected/fox_atlamal.w.unicode_words.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn graphemes_chars_graphemes() -> Result<()> {
+    run(
+        &["--chars", "--graphemes", GRAPHEMES],
+        "tests/expected/graphemes.txt.mg.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_graphemes_graphemes() -> Result<()> {
+    run(
+        &["-g", FOX, GRAPHEMES],
+        "tests/expected/fox_graphemes.g.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tree_recursive() -> Result<()> {
+    run(&["-r", "tests/inputs/tree"], "tests/expected/tree.r.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tree_recursive_include() -> Result<()> {
+    run(
+        &["--recursive", "--include", "*.txt", "tests/inputs/tree"],
+        "tests/expected/tree.r.include_txt.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_include_without_recursive() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--include", "*.txt", "tests/inputs/tree"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--recursive"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn atlamal_stdin_name() -> Result<()> {
+    let input = fs::read_to_string(ATLAMAL)?;
+    Command::cargo_bin(PRG)?
+        .args(["--stdin-name", "atlamal"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("      4      29     177 atlamal\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_stdin_name_csv() -> Result<()> {
+    let input = fs::read_to_string(FOX)?;
+    Command::cargo_bin(PRG)?
+        .args(["--stdin-name", "piped", "--format", "csv", "-l", "-", FOX])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("name,lines\npiped,1\ntests/inputs/fox.txt,1\ntotal,2\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn thresholds_pass() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--fail-if-lines-over", "4", "--fail-if-bytes-under", "48"])
+        .args([FOX, ATLAMAL])
+        .assert()
+        .success();
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_lines_over() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/all.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--fail-if-lines-over", "3", EMPTY, FOX, ATLAMAL])
+        .assert()
+        .failure()
+        .stdout(expected)
+        .stderr("tests/inputs/atlamal.txt: 4 lines is over the limit of 3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_under_and_over() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--fail-if-bytes-under", "1", "--fail-if-bytes-over", "100"])
+        .args([EMPTY, FOX, ATLAMAL])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/inputs/empty.txt: 0 bytes is under the limit of 1",
+        ))
+        .stderr(predicate::str::contains(
+            "tests/inputs/atlamal.txt: 177 bytes is over the limit of 100",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_lines_under() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--fail-if-lines-under", "2", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/inputs/fox.txt: 1 lines is under the limit of 2",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_utf16le_encoding() -> Result<()> {
+    run(
+        &[
+            "-lwm",
+            "--encoding",
+            "utf16le",
+            "tests/inputs/fox.utf16le.txt",
+        ],
+        "tests/expected/fox.utf16le.txt.lwm.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_utf16le_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([
+            "-c",
+            "--encoding",
+            "utf16le",
+            "tests/inputs/fox.utf16le.txt",
+        ])
+        .assert()
+        .success()
+        .stdout("96 tests/inputs/fox.utf16le.txt\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_encoding() -> Result<()> {
+    run(
+        &["-m", "--encoding", "latin1", "tests/inputs/latin1.txt"],
+        "tests/expected/latin1.txt.m.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_lossy_encoding() -> Result<()> {
+    run(
+        &["-m", "--encoding", "utf8", "tests/inputs/invalid.txt"],
+        "tests/expected/invalid.txt.m.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "tests/inputs/invalid.txt"])
+        .assert()
+        .success()
+        .stdout("9 tests/inputs/invalid.txt\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_strict_encoding() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-m", "--encoding", "utf8", "--strict-encoding"])
+        .arg("tests/inputs/invalid.txt")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("tests/inputs/invalid.txt: invalid UTF-8 byte sequence\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bom_count() -> Result<()> {
+    run(
+        &["-m", "--bom", "count", "tests/inputs/bom-utf8.txt"],
+        "tests/expected/bom-utf8.txt.m.count.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bom_skip_chars() -> Result<()> {
+    run(
+        &[
+            "-lwm",
+            "--bom",
+            "skip",
+            "tests/inputs/bom-utf8.txt",
+            "tests/inputs/bom-utf16le.txt",
+        ],
+        "tests/expected/bom.lwm.skip.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bom_skip_bytes() -> Result<()> {
+    run(
+        &[
+            "-c",
+            "--bom",
+            "skip",
+            "tests/inputs/bom-utf8.txt",
+            "tests/inputs/bom-utf16le.txt",
+        ],
+        "tests/expected/bom.c.skip.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bom_error() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--bom", "error", "tests/inputs/bom-utf8.txt", FOX])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tests/inputs/fox.txt"))
+        .stdout(predicate::str::contains("bom-utf8.txt").not())
+        .stderr("tests/inputs/bom-utf8.txt: UTF-8 byte order mark found\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tree_glob() -> Result<()> {
+    run(
+        &["--glob", "tests/inputs/tree/**/*.txt"],
+        "tests/expected/tree.glob_txt.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_glob_without_matches() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--glob", FOX, "tests/inputs/*.nope"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("tests/inputs/*.nope: no files match\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_glob() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--glob", "tests/inputs/***"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("tests/inputs/***: "));
+    Ok(())
+}